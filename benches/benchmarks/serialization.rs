@@ -3,6 +3,11 @@ use serde::Serialize;
 
 use track::serialization::{ModificationSerializer, SerializationStrategy};
 use track::serialization::bincode::Bincode;
+#[cfg(feature = "cbor-serialization")]
+use track::serialization::cbor::Cbor;
+#[cfg(feature = "postcard-serialization")]
+use track::serialization::postcard::Postcard;
+#[cfg(feature = "rmp-serialization")]
 use track::serialization::rmp::Rmp;
 
 #[derive(Clone, Serialize)]
@@ -18,7 +23,25 @@ fn serialize<T: SerializationStrategy, C: Serialize>(
     serializer.serialize(&serializable);
 }
 
+fn print_payload_size<T: SerializationStrategy>(name: &str, strategy: T) {
+    let packet = Position { x: 12.5, y: 33.6 };
+    let size = ModificationSerializer::new(strategy)
+        .serialize(&packet)
+        .map(|data| data.len())
+        .unwrap_or(0);
+
+    println!("Payload size with {}: {} bytes", name, size);
+}
+
 pub fn criterion_benchmark(c: &mut Criterion) {
+    print_payload_size("bincode", Bincode);
+    #[cfg(feature = "rmp-serialization")]
+    print_payload_size("rmp-serde", Rmp);
+    #[cfg(feature = "cbor-serialization")]
+    print_payload_size("ciborium", Cbor);
+    #[cfg(feature = "postcard-serialization")]
+    print_payload_size("postcard", Postcard);
+
     c.bench_function("Serialization with bincode", |b| {
         let serializer = ModificationSerializer::new(Bincode);
         let packet = Position { x: 12.5, y: 33.6 };
@@ -26,12 +49,29 @@ pub fn criterion_benchmark(c: &mut Criterion) {
         b.iter(|| serialize::<Bincode, Position>(&serializer, packet.clone()));
     });
 
+    #[cfg(feature = "rmp-serialization")]
     c.bench_function("Serialization with rmp-serde", |b| {
         let serializer = ModificationSerializer::new(Rmp);
         let packet = Position { x: 12.5, y: 33.6 };
 
         b.iter(|| serialize::<Rmp, Position>(&serializer, packet.clone()));
     });
+
+    #[cfg(feature = "cbor-serialization")]
+    c.bench_function("Serialization with ciborium", |b| {
+        let serializer = ModificationSerializer::new(Cbor);
+        let packet = Position { x: 12.5, y: 33.6 };
+
+        b.iter(|| serialize::<Cbor, Position>(&serializer, packet.clone()));
+    });
+
+    #[cfg(feature = "postcard-serialization")]
+    c.bench_function("Serialization with postcard", |b| {
+        let serializer = ModificationSerializer::new(Postcard);
+        let packet = Position { x: 12.5, y: 33.6 };
+
+        b.iter(|| serialize::<Postcard, Position>(&serializer, packet.clone()));
+    });
 }
 
 criterion_group!(serialization, criterion_benchmark);
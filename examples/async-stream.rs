@@ -0,0 +1,57 @@
+use futures::{channel::mpsc, executor::block_on, join, StreamExt};
+use track::{Apply, ModificationChannel, preclude::*, serialization::bincode::Bincode};
+
+#[track(serialization = "Bincode")]
+#[derive(Debug, PartialEq)]
+pub struct Position {
+    pub x: u32,
+    pub y: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Identity {
+    pub value: u8,
+}
+
+fn main() {
+    let identity = Identity { value: 1 };
+    let mut position = Position { x: 0, y: 0 };
+    let mut replica = position.clone();
+
+    // == Receive the events of a `ModificationChannel` as a `Stream` ==
+    let channel = ModificationChannel::<Identity>::new();
+
+    block_on(async {
+        let mut stream = channel.stream();
+
+        let consumer = async {
+            // Waits without blocking until the producer sends the event.
+            let event = stream.next().await.unwrap();
+            Apply::apply_to(&mut replica, &event.modified_fields, Bincode).unwrap();
+        };
+
+        let producer = async {
+            let mut position = position.track_on(&channel, identity);
+            position.x += 1;
+        }; // <- on the `Drop` of the tracker the event is sent, which wakes the consumer.
+
+        join!(consumer, producer);
+    });
+
+    assert_eq!(position, replica);
+
+    // == Send the events to a futures channel, the tracker never blocks on drop ==
+    let (sender, mut receiver) = mpsc::unbounded();
+
+    {
+        let mut position = position.track_on(&sender, identity);
+        position.y += 1;
+    }
+
+    block_on(async {
+        let event = receiver.next().await.unwrap();
+        Apply::apply_to(&mut replica, &event.modified_fields, Bincode).unwrap();
+    });
+
+    assert_eq!(position, replica);
+}
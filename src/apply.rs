@@ -1,4 +1,7 @@
+use std::collections::HashMap;
+
 use crate::{SerdeDiff, serialization::SerializationStrategy};
+use crate::{event::PayloadKind, hash, ModificationEvent, TrackableMarker, TypeKey};
 use crate::error::ErrorKind;
 
 /// Applies modified values to a type.
@@ -20,4 +23,327 @@ impl Apply {
     ) -> Result<(), ErrorKind> {
         strategy.apply_to(component, data)
     }
+
+    /// Applies the modified values of an event to a type, after checking that the type has the state the modifications were made on.
+    ///
+    /// If the event carries a [base hash](./struct.ModificationEvent.html#structfield.base_hash) and it differs from the hash of `component`,
+    /// [ErrorKind::BaseMismatch](./error/enum.ErrorKind.html#variant.BaseMismatch) is returned and nothing is applied.
+    /// The component should then be resynchronized, for example with a snapshot.
+    /// The hash requires a deterministic serialization, see [Tracker::with_base_hash](./struct.Tracker.html#method.with_base_hash).
+    ///
+    /// If the event carries a [snapshot](./enum.PayloadKind.html#variant.Snapshot), the whole component is replaced without a check.
+    /// The entries of a [batch](./enum.PayloadKind.html#variant.Batch) are applied in order,
+    /// if one of them fails, the entries before it remain applied.
+    /// If the event was made for another type, [ErrorKind::TypeMismatch](./error/enum.ErrorKind.html#variant.TypeMismatch) is returned.
+    ///
+    /// * `type`: the type to which you want to apply the modified values.
+    /// * `event`: the event with the modified type values.
+    /// * `strategy`: the strategy the event was serialized with.
+    pub fn apply_checked<
+        C: TrackableMarker,
+        S: SerializationStrategy,
+        I: Copy + Clone + Send + Sync,
+    >(
+        component: &mut C,
+        event: &ModificationEvent<I>,
+        strategy: S,
+    ) -> Result<(), ErrorKind> {
+        let type_key = C::type_key()?;
+        if event.type_key != type_key {
+            return Err(ErrorKind::TypeMismatch {
+                expected: type_key,
+                found: event.type_key,
+            });
+        }
+
+        if event.payload == PayloadKind::Snapshot {
+            *component = strategy.deserialize(&event.modified_fields)?;
+            return Ok(());
+        }
+
+        if let Some(expected) = event.base_hash {
+            let found = hash::base_hash(component, &strategy)?;
+
+            if found != expected {
+                return Err(ErrorKind::BaseMismatch { expected, found });
+            }
+        }
+
+        for entry in event.batch_entries() {
+            match entry? {
+                (PayloadKind::Snapshot, data) => *component = strategy.deserialize(data)?,
+                (_, data) => strategy.apply_to(component, data)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+type ApplyFn<I, S, W> =
+    Box<dyn Fn(&mut W, &ModificationEvent<I>, &S) -> Result<bool, ErrorKind> + Send + Sync>;
+
+/// Applies [modification events](./struct.ModificationEvent.html) of different types to the right instances.
+///
+/// Each trackable type is registered with a lookup function that finds the instance for an identifier in a storage `W`.
+/// Events are routed to these functions by their [type key](./struct.TypeKey.html),
+/// so events that were received from other processes are routed as well.
+/// This removes the need to match each event against all types by hand when a channel carries multiple types.
+pub struct ApplyRegistry<I, S, W>
+where
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    strategy: S,
+    appliers: HashMap<TypeKey, ApplyFn<I, S, W>>,
+}
+
+impl<I, S, W> ApplyRegistry<I, S, W>
+where
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    /// Constructs a new registry that applies events with the given strategy.
+    pub fn new(strategy: S) -> ApplyRegistry<I, S, W> {
+        ApplyRegistry {
+            strategy,
+            appliers: HashMap::new(),
+        }
+    }
+
+    /// Registers type `C` with a function that looks up the instance belonging to an identifier.
+    ///
+    /// Returns [ErrorKind::DuplicateTypeKey](./error/enum.ErrorKind.html#variant.DuplicateTypeKey) if the type key of `C` is already registered,
+    /// by `C` or by another type with the same key.
+    pub fn register<C, F>(&mut self, lookup: F) -> Result<&mut ApplyRegistry<I, S, W>, ErrorKind>
+    where
+        C: TrackableMarker,
+        F: for<'w> Fn(&'w mut W, &I) -> Option<&'w mut C> + Send + Sync + 'static,
+        I: 'static,
+        S: 'static,
+        W: 'static,
+    {
+        let type_key = C::type_key()?;
+        if self.appliers.contains_key(&type_key) {
+            return Err(ErrorKind::DuplicateTypeKey(type_key));
+        }
+
+        self.appliers.insert(
+            type_key,
+            Box::new(
+                move |storage: &mut W, event: &ModificationEvent<I>, strategy: &S| {
+                    match lookup(storage, &event.identifier) {
+                        Some(component) => {
+                            Apply::apply_checked(component, event, strategy.clone()).map(|_| true)
+                        }
+                        None => Ok(false),
+                    }
+                },
+            ),
+        );
+        Ok(self)
+    }
+
+    /// Returns whether type `C` is registered.
+    pub fn is_registered<C: TrackableMarker>(&self) -> bool {
+        matches!(C::type_key(), Ok(type_key) if self.appliers.contains_key(&type_key))
+    }
+
+    /// Applies the event to the instance it belongs to.
+    ///
+    /// Returns `false` if the type of the event is not registered or if no instance was found for its identifier.
+    pub fn apply(
+        &self,
+        storage: &mut W,
+        event: &ModificationEvent<I>,
+    ) -> Result<bool, ErrorKind> {
+        match self.appliers.get(&event.type_key) {
+            Some(apply) => apply(storage, event, &self.strategy),
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use crossbeam_channel::unbounded;
+
+    use super::{Apply, ApplyRegistry};
+    use crate::{
+        error::ErrorKind,
+        fixtures::{Health, Position},
+        serialization::bincode::Bincode,
+        ModificationEvent, TrackableMarker, Tracker, TypeKey,
+    };
+
+    mod other {
+        use serde::{Deserialize, Serialize};
+        use serde_diff::SerdeDiff;
+
+        use crate::TrackableMarker;
+
+        /// A type with the same serde name as the `Position` fixture.
+        #[derive(Clone, Debug, Default, SerdeDiff, Serialize, Deserialize)]
+        pub(super) struct Position {
+            pub(super) z: u32,
+        }
+
+        impl TrackableMarker for Position {}
+    }
+
+    #[derive(Default)]
+    struct World {
+        positions: Vec<Position>,
+        healths: Vec<Health>,
+    }
+
+    fn registry() -> ApplyRegistry<u32, Bincode, World> {
+        let mut registry = ApplyRegistry::new(Bincode);
+        registry
+            .register::<Position, _>(|world: &mut World, identifier: &u32| {
+                world.positions.get_mut(*identifier as usize)
+            })
+            .unwrap()
+            .register::<Health, _>(|world: &mut World, identifier: &u32| {
+                world.healths.get_mut(*identifier as usize)
+            })
+            .unwrap();
+        registry
+    }
+
+    /// Returns the event of modifying a copy of `value` with `modify`.
+    fn event_of<C, F>(mut value: C, identifier: u32, modify: F) -> ModificationEvent<u32>
+    where
+        C: TrackableMarker,
+        F: FnOnce(&mut C),
+    {
+        let (sender, receiver) = unbounded();
+
+        let mut tracker = Tracker::new(&mut value, &sender, Bincode, identifier);
+        modify(&mut tracker);
+        drop(tracker);
+
+        receiver.try_recv().unwrap()
+    }
+
+    /// Returns the event of setting `x` on `value`, with a base hash.
+    fn tracked_event(mut value: Position, x: u32) -> ModificationEvent<u32> {
+        let (sender, receiver) = unbounded();
+
+        let mut tracker = Tracker::new(&mut value, &sender, Bincode, 1).with_base_hash();
+        tracker.x = x;
+        drop(tracker);
+
+        receiver.try_recv().unwrap()
+    }
+
+    #[test]
+    fn base_hash_match_applies() {
+        let event = tracked_event(Position { x: 1, y: 2 }, 3);
+        let mut replica = Position { x: 1, y: 2 };
+
+        Apply::apply_checked(&mut replica, &event, Bincode).unwrap();
+
+        assert_eq!(replica, Position { x: 3, y: 2 });
+    }
+
+    #[test]
+    fn base_hash_mismatch_is_rejected() {
+        let event = tracked_event(Position { x: 1, y: 2 }, 3);
+        let mut replica = Position { x: 1, y: 7 };
+
+        match Apply::apply_checked(&mut replica, &event, Bincode) {
+            Err(ErrorKind::BaseMismatch { expected, found }) => assert_ne!(expected, found),
+            other => panic!("expected a base mismatch, got {:?}", other),
+        }
+        assert_eq!(replica, Position { x: 1, y: 7 });
+    }
+
+    #[test]
+    fn snapshot_bypasses_base_hash() {
+        let mut event =
+            ModificationEvent::snapshot(&Position { x: 3, y: 4 }, 1u32, &Bincode).unwrap();
+        event.base_hash = Some(0);
+        let mut replica = Position { x: 1, y: 7 };
+
+        Apply::apply_checked(&mut replica, &event, Bincode).unwrap();
+
+        assert_eq!(replica, Position { x: 3, y: 4 });
+    }
+
+    #[test]
+    fn registry_applies_a_mixed_stream() {
+        let registry = registry();
+        let mut world = World {
+            positions: vec![Position::default(); 2],
+            healths: vec![Health::default(); 2],
+        };
+
+        let events = vec![
+            event_of(Position::default(), 1, |position| position.x = 4),
+            event_of(Health::default(), 0, |health| health.value = 9),
+            event_of(Position::default(), 0, |position| position.y = 2),
+            event_of(Health::default(), 1, |health| health.value = 3),
+        ];
+        for event in &events {
+            assert!(registry.apply(&mut world, event).unwrap());
+        }
+
+        assert_eq!(
+            world.positions,
+            vec![Position { x: 0, y: 2 }, Position { x: 4, y: 0 }]
+        );
+        assert_eq!(
+            world.healths,
+            vec![Health { value: 9 }, Health { value: 3 }]
+        );
+    }
+
+    #[test]
+    fn unregistered_type_is_skipped() {
+        let mut registry = ApplyRegistry::new(Bincode);
+        registry
+            .register::<Position, _>(|world: &mut World, identifier: &u32| {
+                world.positions.get_mut(*identifier as usize)
+            })
+            .unwrap();
+        let mut world = World {
+            positions: vec![Position::default()],
+            healths: vec![Health::default()],
+        };
+
+        let event = event_of(Health::default(), 0, |health| health.value = 9);
+
+        assert!(!registry.is_registered::<Health>());
+        assert!(!registry.apply(&mut world, &event).unwrap());
+        assert_eq!(world.healths, vec![Health::default()]);
+    }
+
+    #[test]
+    fn missing_identifier_is_skipped() {
+        let registry = registry();
+        let mut world = World::default();
+
+        let event = event_of(Position::default(), 3, |position| position.x = 4);
+
+        assert!(!registry.apply(&mut world, &event).unwrap());
+    }
+
+    #[test]
+    fn duplicate_type_key_is_rejected() {
+        let mut registry = registry();
+
+        match registry.register::<Position, _>(|world: &mut World, identifier: &u32| {
+            world.positions.get_mut(*identifier as usize)
+        }) {
+            Err(ErrorKind::DuplicateTypeKey(type_key)) => {
+                assert_eq!(type_key, TypeKey::of::<Position>().unwrap())
+            }
+            other => panic!("expected a duplicate type key, got {:?}", other.err()),
+        }
+
+        assert!(registry
+            .register::<other::Position, _>(|_: &mut World, _: &u32| None)
+            .is_err());
+    }
 }
@@ -0,0 +1,328 @@
+use std::{
+    hash::Hash,
+    sync::{Arc, Mutex, Weak},
+};
+
+use crossbeam_channel::{Receiver, Sender, unbounded};
+
+use crate::{
+    channel::Sequencer,
+    error::{ErrorKind, ErrorPolicy},
+    event::ModificationEvent,
+    ModificationSink, TrackableMarker,
+};
+
+type Filter<I> = Box<dyn Fn(&ModificationEvent<I>) -> bool + Send + Sync>;
+
+/// Identifies a [Subscription](./struct.Subscription.html) on a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+/// A subscription on a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html), which receives every event sent on the channel.
+///
+/// Dropping the subscription unsubscribes it.
+pub struct Subscription<I: Copy + Clone + Send + Sync> {
+    id: SubscriptionId,
+    receiver: Receiver<ModificationEvent<I>>,
+    /// Keeps the subscriber registered for as long as the subscription exists.
+    _alive: Arc<()>,
+}
+
+impl<I: Copy + Clone + Send + Sync> Subscription<I> {
+    /// Returns the id of this subscription.
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    /// Returns a receiver on which the modification events can be received.
+    pub fn receiver(&self) -> &Receiver<ModificationEvent<I>> {
+        &self.receiver
+    }
+}
+
+/// A subscriber that is registered on the channel.
+struct Subscriber<I: Copy + Clone + Send + Sync> {
+    id: SubscriptionId,
+    sender: Sender<ModificationEvent<I>>,
+    /// Selects the events the subscriber receives, all events if `None`.
+    filter: Option<Filter<I>>,
+    /// Is dangling once the subscription is dropped.
+    ///
+    /// The sender only notices that the receiver is dropped when an event is sent to it,
+    /// which may never happen for a subscriber with a filter.
+    subscription: Weak<()>,
+}
+
+impl<I: Copy + Clone + Send + Sync> Subscriber<I> {
+    fn is_subscribed(&self) -> bool {
+        self.subscription.strong_count() > 0
+    }
+}
+
+/// The state that is shared by the senders of a channel.
+struct BroadcastState<I: Copy + Clone + Send + Sync> {
+    sequencer: Sequencer<I>,
+    subscribers: Vec<Subscriber<I>>,
+    last_id: u64,
+}
+
+/// An event channel that delivers every modification event to all of its subscribers.
+///
+/// Subscribers can be added with [subscribe](./struct.BroadcastModificationChannel.html#method.subscribe) and removed at any time,
+/// which allows multiple independent consumers, such as a network replicator, a journal writer and an inspector, to receive the same events.
+/// Subscribers that only need some of the events can select them by [type](./struct.BroadcastModificationChannel.html#method.subscribe_type),
+/// by [identifier](./struct.BroadcastModificationChannel.html#method.subscribe_identifier) or with a [predicate](./struct.BroadcastModificationChannel.html#method.subscribe_filtered).
+/// Events are assigned a sequence number and version like on a [ModificationChannel](./struct.ModificationChannel.html).
+/// Events that are sent while there are no subscribers are discarded.
+pub struct BroadcastModificationChannel<I: Copy + Clone + Send + Sync> {
+    state: Mutex<BroadcastState<I>>,
+    error_policy: ErrorPolicy,
+}
+
+impl<I: Copy + Clone + Send + Sync> BroadcastModificationChannel<I> {
+    /// Constructs a new broadcast channel without subscribers.
+    pub fn new() -> BroadcastModificationChannel<I> {
+        BroadcastModificationChannel {
+            state: Mutex::new(BroadcastState {
+                sequencer: Sequencer::new(),
+                subscribers: Vec::new(),
+                last_id: 0,
+            }),
+            error_policy: ErrorPolicy::default(),
+        }
+    }
+
+    /// Sets the [ErrorPolicy](./error/enum.ErrorPolicy.html) for trackers created with [Trackable::track_on](./trait.Trackable.html#method.track_on).
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> BroadcastModificationChannel<I> {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Adds a subscriber that receives all events that are sent from now on.
+    pub fn subscribe(&self) -> Subscription<I> {
+        self.add_subscriber(None)
+    }
+
+    /// Adds a subscriber that receives the events, sent from now on, for which the predicate returns `true`.
+    ///
+    /// The predicate is called for each event on the thread that sends it, so it should be cheap.
+    pub fn subscribe_filtered<F>(&self, predicate: F) -> Subscription<I>
+    where
+        F: Fn(&ModificationEvent<I>) -> bool + Send + Sync + 'static,
+    {
+        self.add_subscriber(Some(Box::new(predicate)))
+    }
+
+    /// Adds a subscriber that receives the events of type `C` that are sent from now on.
+    ///
+    /// The events are selected by their [type key](./struct.TypeKey.html).
+    pub fn subscribe_type<C: TrackableMarker>(&self) -> Result<Subscription<I>, ErrorKind> {
+        let type_key = C::type_key()?;
+        Ok(self.add_subscriber(Some(Box::new(move |event: &ModificationEvent<I>| {
+            event.type_key == type_key
+        }))))
+    }
+
+    /// Adds a subscriber that receives the events of the given identifier that are sent from now on.
+    pub fn subscribe_identifier(&self, identifier: I) -> Subscription<I>
+    where
+        I: PartialEq + 'static,
+    {
+        self.add_subscriber(Some(Box::new(move |event: &ModificationEvent<I>| {
+            event.identifier == identifier
+        })))
+    }
+
+    fn add_subscriber(&self, filter: Option<Filter<I>>) -> Subscription<I> {
+        let (tx, rx) = unbounded();
+        let alive = Arc::new(());
+
+        let mut state = self.lock();
+        state.last_id += 1;
+        let id = SubscriptionId(state.last_id);
+        state.subscribers.push(Subscriber {
+            id,
+            sender: tx,
+            filter,
+            subscription: Arc::downgrade(&alive),
+        });
+
+        Subscription {
+            id,
+            receiver: rx,
+            _alive: alive,
+        }
+    }
+
+    /// Removes the subscriber with the given id, events that it has not yet received remain available on its receiver.
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.lock().subscribers.retain(|subscriber| subscriber.id != id);
+    }
+
+    /// Returns the number of subscribers, subscriptions that have been dropped are removed.
+    pub fn subscriber_count(&self) -> usize {
+        let mut state = self.lock();
+        state.subscribers.retain(Subscriber::is_subscribed);
+        state.subscribers.len()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BroadcastState<I>> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync> Default for BroadcastModificationChannel<I> {
+    fn default() -> Self {
+        BroadcastModificationChannel::new()
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> BroadcastModificationChannel<I> {
+    /// Assigns the next sequence number and version to the event and sends it to all subscribers that select it.
+    ///
+    /// Subscriptions that have been dropped are removed.
+    /// Returns `Ok` even if there are no subscribers, the event is then discarded,
+    /// because subscribers are expected to come and go while the channel is in use.
+    pub fn send(&self, mut event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        let mut state = self.lock();
+        state.sequencer.stamp(&mut event);
+
+        // The lock is held while sending to keep the events on the channel in sequence.
+        state.subscribers.retain(|subscriber| {
+            if !subscriber.is_subscribed() {
+                return false;
+            }
+
+            match &subscriber.filter {
+                Some(filter) if !filter(&event) => true,
+                _ => subscriber.sender.send(event.clone()).is_ok(),
+            }
+        });
+
+        Ok(())
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> ModificationSink<I>
+    for BroadcastModificationChannel<I>
+{
+    fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        BroadcastModificationChannel::send(self, event)
+    }
+
+    fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::any::TypeId;
+
+    use super::BroadcastModificationChannel;
+    use crate::{
+        fixtures::{Health, Position},
+        serialization::bincode::Bincode,
+        ModificationEvent, TypeKey,
+    };
+
+    fn event(identifier: u32) -> ModificationEvent<u32> {
+        ModificationEvent::new(
+            Vec::new(),
+            identifier,
+            TypeId::of::<Position>(),
+            TypeKey::from_raw(1),
+        )
+    }
+
+    fn identifiers(receiver: &crossbeam_channel::Receiver<ModificationEvent<u32>>) -> Vec<u32> {
+        receiver.try_iter().map(|event| event.identifier).collect()
+    }
+
+    #[test]
+    fn every_subscriber_receives_every_event() {
+        let channel = BroadcastModificationChannel::new();
+        let first = channel.subscribe();
+        let second = channel.subscribe();
+
+        channel.send(event(1)).unwrap();
+        channel.send(event(2)).unwrap();
+
+        assert_eq!(identifiers(first.receiver()), vec![1, 2]);
+        assert_eq!(identifiers(second.receiver()), vec![1, 2]);
+    }
+
+    #[test]
+    fn unsubscribe_stops_delivery_and_keeps_queued_events() {
+        let channel = BroadcastModificationChannel::new();
+        let subscription = channel.subscribe();
+
+        channel.send(event(1)).unwrap();
+        channel.unsubscribe(subscription.id());
+        channel.send(event(2)).unwrap();
+
+        assert_eq!(channel.subscriber_count(), 0);
+        assert_eq!(identifiers(subscription.receiver()), vec![1]);
+    }
+
+    #[test]
+    fn dropped_subscription_is_removed() {
+        let channel = BroadcastModificationChannel::new();
+        let kept = channel.subscribe();
+        drop(channel.subscribe());
+
+        channel.send(event(1)).unwrap();
+
+        assert_eq!(channel.subscriber_count(), 1);
+        assert_eq!(identifiers(kept.receiver()), vec![1]);
+    }
+
+    #[test]
+    fn dropped_filtered_subscription_is_removed() {
+        let channel = BroadcastModificationChannel::new();
+        drop(channel.subscribe_identifier(2));
+
+        channel.send(event(1)).unwrap();
+
+        assert_eq!(channel.lock().subscribers.len(), 0);
+    }
+
+    #[test]
+    fn filtered_subscriptions_receive_only_the_selected_events() {
+        let channel = BroadcastModificationChannel::new();
+        let odd = channel.subscribe_filtered(|event| event.identifier % 2 == 1);
+        let second = channel.subscribe_identifier(2);
+
+        for identifier in 1..=4 {
+            channel.send(event(identifier)).unwrap();
+        }
+
+        assert_eq!(identifiers(odd.receiver()), vec![1, 3]);
+        assert_eq!(identifiers(second.receiver()), vec![2]);
+    }
+
+    #[test]
+    fn type_subscription_receives_only_its_type() {
+        let channel = BroadcastModificationChannel::new();
+        let positions = channel.subscribe_type::<Position>().unwrap();
+        let healths = channel.subscribe_type::<Health>().unwrap();
+
+        channel.send(event(1)).unwrap();
+        channel
+            .send(ModificationEvent::snapshot(&Health { value: 1 }, 2, &Bincode).unwrap())
+            .unwrap();
+        channel
+            .send(ModificationEvent::snapshot(&Position { x: 1, y: 1 }, 3, &Bincode).unwrap())
+            .unwrap();
+
+        assert_eq!(identifiers(positions.receiver()), vec![3]);
+        assert_eq!(identifiers(healths.receiver()), vec![2]);
+    }
+
+    #[test]
+    fn send_without_subscribers_is_ok() {
+        let channel = BroadcastModificationChannel::new();
+
+        assert!(channel.send(event(1)).is_ok());
+    }
+}
@@ -0,0 +1,771 @@
+use std::fmt;
+
+use serde::{
+    ser::{
+        self, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+        SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+    },
+    Serialize, Serializer,
+};
+use serde_json::{Error, Map, Value};
+
+use crate::{
+    error::ErrorKind, serialization::SerializationStrategy, Apply, FieldPathMode,
+    ModificationEvent, TrackableMarker, TypedModificationEvent,
+};
+
+/// A step in the path to a changed field, see [FieldChange](./struct.FieldChange.html).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldPathSegment {
+    /// A field of a struct, identified by its position in the declaration, used with [FieldPathMode::Index](./enum.FieldPathMode.html#variant.Index).
+    ///
+    /// Fields left out with `skip_serializing_if` are counted, but fields that are never serialized,
+    /// with `#[serde(skip)]` or `#[serde(skip_serializing)]`, cannot be seen.
+    /// The position of the fields after them is off by one for each, so use [FieldPathMode::Name](./enum.FieldPathMode.html#variant.Name) for such types.
+    FieldIndex(usize),
+    /// A field of a struct, identified by its name, used with [FieldPathMode::Name](./enum.FieldPathMode.html#variant.Name).
+    FieldName(&'static str),
+    /// An element of a sequence or tuple.
+    Element(usize),
+    /// An entry of a map, identified by its key.
+    /// Keys that are not strings are written as JSON.
+    Key(String),
+    /// The variant of an enum, which is the same before and after the change.
+    Variant(&'static str),
+}
+
+impl fmt::Display for FieldPathSegment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldPathSegment::FieldIndex(index) => write!(f, "{}", index),
+            FieldPathSegment::FieldName(name) => write!(f, "{}", name),
+            FieldPathSegment::Element(index) => write!(f, "[{}]", index),
+            FieldPathSegment::Key(key) => write!(f, "[{:?}]", key),
+            FieldPathSegment::Variant(variant) => write!(f, "<{}>", variant),
+        }
+    }
+}
+
+/// A field that was changed by a modification.
+///
+/// The change lists are computed by comparing the value before and after the modification, see [Apply::field_changes](./struct.Apply.html#method.field_changes).
+/// Only the fields that differ are listed, with the deepest path that contains the whole difference.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldChange {
+    /// The path from the root of the value to the changed field, empty if the value was replaced as a whole.
+    pub path: Vec<FieldPathSegment>,
+    /// The new value of the field, or `None` if it was removed, such as the entry of a map or an element beyond the end of a shorter sequence.
+    pub value: Option<Value>,
+}
+
+impl FieldChange {
+    /// Returns the fields that differ between `old` and `new`.
+    pub fn between<T: Serialize>(
+        old: &T,
+        new: &T,
+        mode: FieldPathMode,
+    ) -> Result<Vec<FieldChange>, ErrorKind> {
+        let old = old
+            .serialize(NodeSerializer)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))?;
+        let new = new
+            .serialize(NodeSerializer)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))?;
+
+        let mut changes = Vec::new();
+        compare(&mut Vec::new(), &old, new, mode, &mut changes);
+
+        Ok(changes)
+    }
+}
+
+impl Apply {
+    /// Returns the fields that the event changes when it is applied to `base`.
+    ///
+    /// The event is applied to a copy of `base` with [apply_checked](./struct.Apply.html#method.apply_checked),
+    /// which is then compared with `base`.
+    /// Fields are identified in the [field path mode](./struct.ModificationEvent.html#structfield.field_path_mode) of the event,
+    /// use [FieldChange::between](./struct.FieldChange.html#method.between) to list them in another mode.
+    /// This requires the `field-changes` feature.
+    pub fn field_changes<C, S, I>(
+        base: &C,
+        event: &ModificationEvent<I>,
+        strategy: S,
+    ) -> Result<Vec<FieldChange>, ErrorKind>
+    where
+        C: TrackableMarker,
+        S: SerializationStrategy,
+        I: Copy + Clone + Send + Sync,
+    {
+        let mut modified = base.clone();
+        Apply::apply_checked(&mut modified, event, strategy)?;
+
+        FieldChange::between(base, &modified, event.field_path_mode)
+    }
+}
+
+impl<C, I> TypedModificationEvent<C, I>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+{
+    /// Returns the fields that this event changes when it is applied to `base`, see [Apply::field_changes](./struct.Apply.html#method.field_changes).
+    pub fn field_changes(&self, base: &C) -> Result<Vec<FieldChange>, ErrorKind> {
+        FieldChange::between(base, &self.applied_to(base)?, self.event().field_path_mode)
+    }
+}
+
+/// Compares two value trees and adds the differences to `changes`.
+fn compare(
+    path: &mut Vec<FieldPathSegment>,
+    old: &Node,
+    new: Node,
+    mode: FieldPathMode,
+    changes: &mut Vec<FieldChange>,
+) {
+    match (old, new) {
+        (Node::Struct(old_fields), Node::Struct(new_fields)) => {
+            let segment = |index: usize, name: &'static str| match mode {
+                FieldPathMode::Index => FieldPathSegment::FieldIndex(index),
+                FieldPathMode::Name => FieldPathSegment::FieldName(name),
+            };
+
+            // Fields can be left out with `skip_serializing_if`.
+            for (index, name, _) in old_fields {
+                if !new_fields.iter().any(|(_, new_name, _)| new_name == name) {
+                    push_change(path, segment(*index, name), None, changes);
+                }
+            }
+
+            for (index, name, new_field) in new_fields {
+                match old_fields.iter().find(|(_, old_name, _)| *old_name == name) {
+                    Some((_, _, old_field)) => {
+                        path.push(segment(index, name));
+                        compare(path, old_field, new_field, mode, changes);
+                        path.pop();
+                    }
+                    None => push_change(path, segment(index, name), Some(new_field), changes),
+                }
+            }
+        }
+        (Node::Seq(old_elements), Node::Seq(new_elements)) => {
+            let new_len = new_elements.len();
+
+            for (index, new_element) in new_elements.into_iter().enumerate() {
+                match old_elements.get(index) {
+                    Some(old_element) => {
+                        path.push(FieldPathSegment::Element(index));
+                        compare(path, old_element, new_element, mode, changes);
+                        path.pop();
+                    }
+                    None => push_change(
+                        path,
+                        FieldPathSegment::Element(index),
+                        Some(new_element),
+                        changes,
+                    ),
+                }
+            }
+
+            for index in new_len..old_elements.len() {
+                push_change(path, FieldPathSegment::Element(index), None, changes);
+            }
+        }
+        (Node::Map(old_entries), Node::Map(new_entries)) => {
+            for (key, _) in old_entries {
+                if !new_entries.iter().any(|(new_key, _)| new_key == key) {
+                    push_change(path, FieldPathSegment::Key(key.clone()), None, changes);
+                }
+            }
+
+            for (key, new_entry) in new_entries {
+                match old_entries.iter().find(|(old_key, _)| *old_key == key) {
+                    Some((_, old_entry)) => {
+                        path.push(FieldPathSegment::Key(key));
+                        compare(path, old_entry, new_entry, mode, changes);
+                        path.pop();
+                    }
+                    None => push_change(path, FieldPathSegment::Key(key), Some(new_entry), changes),
+                }
+            }
+        }
+        (Node::Variant(old_variant, old_inner), Node::Variant(new_variant, new_inner))
+            if *old_variant == new_variant =>
+        {
+            path.push(FieldPathSegment::Variant(new_variant));
+            compare(path, old_inner, *new_inner, mode, changes);
+            path.pop();
+        }
+        (old, new) => {
+            if *old != new {
+                changes.push(FieldChange {
+                    path: path.clone(),
+                    value: Some(new.into_value()),
+                });
+            }
+        }
+    }
+}
+
+/// Adds a change of the field at `segment` below `path`.
+fn push_change(
+    path: &[FieldPathSegment],
+    segment: FieldPathSegment,
+    value: Option<Node>,
+    changes: &mut Vec<FieldChange>,
+) {
+    let mut path = path.to_vec();
+    path.push(segment);
+
+    changes.push(FieldChange {
+        path,
+        value: value.map(Node::into_value),
+    });
+}
+
+/// A serialized value that, unlike a JSON value, tells structs apart from maps and keeps enum variants.
+#[derive(PartialEq)]
+enum Node {
+    Leaf(Value),
+    Seq(Vec<Node>),
+    Map(Vec<(String, Node)>),
+    /// The fields with their position in the declaration and their name.
+    Struct(Vec<(usize, &'static str, Node)>),
+    Variant(&'static str, Box<Node>),
+}
+
+impl Node {
+    /// Converts the node to a JSON value, enums are externally tagged like serde_json does.
+    fn into_value(self) -> Value {
+        match self {
+            Node::Leaf(value) => value,
+            Node::Seq(elements) => Value::Array(elements.into_iter().map(Node::into_value).collect()),
+            Node::Map(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, node)| (key, node.into_value()))
+                    .collect::<Map<_, _>>(),
+            ),
+            Node::Struct(fields) => Value::Object(
+                fields
+                    .into_iter()
+                    .map(|(_, name, node)| (name.to_owned(), node.into_value()))
+                    .collect::<Map<_, _>>(),
+            ),
+            Node::Variant(variant, inner) => {
+                let mut object = Map::new();
+                object.insert(variant.to_owned(), inner.into_value());
+                Value::Object(object)
+            }
+        }
+    }
+
+    /// Returns the node as a map key.
+    fn into_key(self) -> String {
+        match self {
+            Node::Leaf(Value::String(key)) => key,
+            node => node.into_value().to_string(),
+        }
+    }
+}
+
+/// Serializes a value into a tree of nodes.
+struct NodeSerializer;
+
+impl Serializer for NodeSerializer {
+    type Ok = Node;
+    type Error = Error;
+
+    type SerializeSeq = SeqBuilder;
+    type SerializeTuple = SeqBuilder;
+    type SerializeTupleStruct = SeqBuilder;
+    type SerializeTupleVariant = TupleVariantBuilder;
+    type SerializeMap = MapBuilder;
+    type SerializeStruct = StructBuilder;
+    type SerializeStructVariant = StructVariantBuilder;
+
+    fn serialize_bool(self, v: bool) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::Bool(v)))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(f64::from(v))))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::from(v)))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::String(v.to_string())))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::String(v.to_owned())))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Node, Error> {
+        Ok(Node::Seq(
+            v.iter().map(|byte| Node::Leaf(Value::from(*byte))).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::Null))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Node, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::Null))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::Null))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Node, Error> {
+        Ok(Node::Leaf(Value::String(variant.to_owned())))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Node, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Node, Error> {
+        Ok(Node::Variant(variant, Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqBuilder, Error> {
+        Ok(SeqBuilder {
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SeqBuilder, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<TupleVariantBuilder, Error> {
+        Ok(TupleVariantBuilder {
+            variant,
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<MapBuilder, Error> {
+        Ok(MapBuilder {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<StructBuilder, Error> {
+        Ok(StructBuilder {
+            fields: FieldsBuilder::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<StructVariantBuilder, Error> {
+        Ok(StructVariantBuilder {
+            variant,
+            fields: FieldsBuilder::with_capacity(len),
+        })
+    }
+}
+
+struct SeqBuilder {
+    elements: Vec<Node>,
+}
+
+impl SerializeSeq for SeqBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.elements.push(value.serialize(NodeSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Seq(self.elements))
+    }
+}
+
+impl SerializeTuple for SeqBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SeqBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        SerializeSeq::end(self)
+    }
+}
+
+struct TupleVariantBuilder {
+    variant: &'static str,
+    elements: Vec<Node>,
+}
+
+impl SerializeTupleVariant for TupleVariantBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.elements.push(value.serialize(NodeSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Variant(self.variant, Box::new(Node::Seq(self.elements))))
+    }
+}
+
+struct MapBuilder {
+    entries: Vec<(String, Node)>,
+    key: Option<String>,
+}
+
+impl SerializeMap for MapBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(NodeSerializer)?.into_key());
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("Map value serialized before its key"))?;
+        self.entries.push((key, value.serialize(NodeSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Map(self.entries))
+    }
+}
+
+/// Collects the fields of a struct with their position in the declaration.
+struct FieldsBuilder {
+    fields: Vec<(usize, &'static str, Node)>,
+    /// The position of the next field, which counts the skipped fields like serde-diff does.
+    index: usize,
+}
+
+impl FieldsBuilder {
+    fn with_capacity(len: usize) -> FieldsBuilder {
+        FieldsBuilder {
+            fields: Vec::with_capacity(len),
+            index: 0,
+        }
+    }
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.fields
+            .push((self.index, key, value.serialize(NodeSerializer)?));
+        self.index += 1;
+        Ok(())
+    }
+
+    fn skip_field(&mut self) {
+        self.index += 1;
+    }
+}
+
+struct StructBuilder {
+    fields: FieldsBuilder,
+}
+
+impl SerializeStruct for StructBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.fields.serialize_field(key, value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), Error> {
+        self.fields.skip_field();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Struct(self.fields.fields))
+    }
+}
+
+struct StructVariantBuilder {
+    variant: &'static str,
+    fields: FieldsBuilder,
+}
+
+impl SerializeStructVariant for StructVariantBuilder {
+    type Ok = Node;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.fields.serialize_field(key, value)
+    }
+
+    fn skip_field(&mut self, _key: &'static str) -> Result<(), Error> {
+        self.fields.skip_field();
+        Ok(())
+    }
+
+    fn end(self) -> Result<Node, Error> {
+        Ok(Node::Variant(
+            self.variant,
+            Box::new(Node::Struct(self.fields.fields)),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crossbeam_channel::unbounded;
+    use serde::{Deserialize, Serialize};
+    use serde_diff::SerdeDiff;
+    use serde_json::Value;
+
+    use super::{FieldChange, FieldPathSegment};
+    use crate::{
+        fixtures::Position, serialization::bincode::Bincode, Apply, FieldPathMode, TrackableMarker,
+        Tracker, TypedModificationEvent,
+    };
+
+    #[derive(Clone, Debug, Default, PartialEq, SerdeDiff, Serialize, Deserialize)]
+    struct Sparse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        a: Option<u32>,
+        b: u32,
+    }
+
+    impl TrackableMarker for Sparse {}
+
+    #[derive(Clone, Debug, Default, PartialEq, Serialize)]
+    struct Inventory {
+        items: Vec<u32>,
+        counts: BTreeMap<String, u32>,
+    }
+
+    #[test]
+    fn fields_are_identified_in_the_given_mode() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 1, y: 3 };
+
+        assert_eq!(
+            FieldChange::between(&old, &new, FieldPathMode::Index).unwrap(),
+            vec![FieldChange {
+                path: vec![FieldPathSegment::FieldIndex(1)],
+                value: Some(Value::from(3)),
+            }]
+        );
+        assert_eq!(
+            FieldChange::between(&old, &new, FieldPathMode::Name).unwrap(),
+            vec![FieldChange {
+                path: vec![FieldPathSegment::FieldName("y")],
+                value: Some(Value::from(3)),
+            }]
+        );
+    }
+
+    #[test]
+    fn field_index_counts_skipped_fields() {
+        let old = Sparse { a: None, b: 1 };
+        let new = Sparse { a: None, b: 2 };
+
+        let changes = FieldChange::between(&old, &new, FieldPathMode::Index).unwrap();
+
+        assert_eq!(changes[0].path, vec![FieldPathSegment::FieldIndex(1)]);
+    }
+
+    #[test]
+    fn skipped_field_that_is_set_is_added() {
+        let old = Sparse { a: None, b: 1 };
+        let new = Sparse { a: Some(4), b: 1 };
+
+        assert_eq!(
+            FieldChange::between(&old, &new, FieldPathMode::Index).unwrap(),
+            vec![FieldChange {
+                path: vec![FieldPathSegment::FieldIndex(0)],
+                value: Some(Value::from(4)),
+            }]
+        );
+        assert_eq!(
+            FieldChange::between(&new, &old, FieldPathMode::Index).unwrap(),
+            vec![FieldChange {
+                path: vec![FieldPathSegment::FieldIndex(0)],
+                value: None,
+            }]
+        );
+    }
+
+    #[test]
+    fn elements_and_entries_are_listed() {
+        let old = Inventory {
+            items: vec![1, 2, 3],
+            counts: vec![("apple".to_owned(), 1), ("pear".to_owned(), 2)]
+                .into_iter()
+                .collect(),
+        };
+        let new = Inventory {
+            items: vec![1, 5],
+            counts: vec![("apple".to_owned(), 3)].into_iter().collect(),
+        };
+
+        let changes = FieldChange::between(&old, &new, FieldPathMode::Name).unwrap();
+
+        let items = FieldPathSegment::FieldName("items");
+        let counts = FieldPathSegment::FieldName("counts");
+        assert_eq!(
+            changes,
+            vec![
+                FieldChange {
+                    path: vec![items.clone(), FieldPathSegment::Element(1)],
+                    value: Some(Value::from(5)),
+                },
+                FieldChange {
+                    path: vec![items, FieldPathSegment::Element(2)],
+                    value: None,
+                },
+                FieldChange {
+                    path: vec![counts.clone(), FieldPathSegment::Key("pear".to_owned())],
+                    value: None,
+                },
+                FieldChange {
+                    path: vec![counts, FieldPathSegment::Key("apple".to_owned())],
+                    value: Some(Value::from(3)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn event_changes_use_the_mode_of_the_event() {
+        let base = Sparse { a: None, b: 1 };
+        let (sender, receiver) = unbounded();
+
+        for &mode in &[FieldPathMode::Index, FieldPathMode::Name] {
+            let mut value = base.clone();
+            let mut tracker =
+                Tracker::new(&mut value, &sender, Bincode, 1_u32).with_field_path_mode(mode);
+            tracker.b = 2;
+            drop(tracker);
+            let event = receiver.try_recv().unwrap();
+
+            let expected = match mode {
+                FieldPathMode::Index => FieldPathSegment::FieldIndex(1),
+                FieldPathMode::Name => FieldPathSegment::FieldName("b"),
+            };
+            let changes = Apply::field_changes(&base, &event, Bincode).unwrap();
+            assert_eq!(changes[0].path, vec![expected.clone()]);
+
+            let typed =
+                TypedModificationEvent::<Sparse, u32>::from_event::<Bincode>(event).unwrap();
+            assert_eq!(typed.field_changes(&base).unwrap()[0].path, vec![expected]);
+        }
+    }
+}
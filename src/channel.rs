@@ -1,11 +1,103 @@
-use crossbeam_channel::{Receiver, Sender, unbounded};
+use std::{collections::HashMap, hash::Hash, sync::Mutex};
 
-use crate::event::ModificationEvent;
+use crossbeam_channel::{Receiver, Sender, TrySendError, bounded, unbounded};
+
+#[cfg(feature = "async")]
+use crate::stream::Wakers;
+use crate::{
+    error::{ErrorKind, ErrorPolicy},
+    event::ModificationEvent,
+    TypeKey,
+};
+
+/// The error policy of sinks that do not configure one.
+static DEFAULT_ERROR_POLICY: ErrorPolicy = ErrorPolicy::Panic;
+
+/// A destination for modification events, such as a [ModificationChannel](./struct.ModificationChannel.html) or a crossbeam [Sender](https://docs.rs/crossbeam-channel/0.4.0/crossbeam_channel/struct.Sender.html).
+///
+/// A [Tracker](./struct.Tracker.html) sends its events to a sink.
+pub trait ModificationSink<I: Copy + Clone + Send + Sync>: Send + Sync {
+    /// Sends the given event.
+    fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind>;
+
+    /// Returns the [ErrorPolicy](./error/enum.ErrorPolicy.html) for trackers created with [Trackable::track_on](./trait.Trackable.html#method.track_on).
+    fn error_policy(&self) -> &ErrorPolicy {
+        &DEFAULT_ERROR_POLICY
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync> ModificationSink<I> for Sender<ModificationEvent<I>> {
+    fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        Sender::send(self, event).map_err(|_| ErrorKind::ChannelDisconnected)
+    }
+}
+
+/// Keeps track of the sequence numbers and versions that are assigned to events.
+pub(crate) struct Sequencer<I> {
+    last_sequence: u64,
+    versions: HashMap<(I, TypeKey), u64>,
+}
+
+impl<I> Sequencer<I> {
+    pub(crate) fn new() -> Sequencer<I> {
+        Sequencer {
+            last_sequence: 0,
+            versions: HashMap::new(),
+        }
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> Sequencer<I> {
+    /// Assigns the next sequence number, and the next version of its identifier and type, to the event.
+    pub(crate) fn stamp(&mut self, event: &mut ModificationEvent<I>) {
+        self.last_sequence += 1;
+        event.sequence = self.last_sequence;
+
+        let version = self
+            .versions
+            .entry((event.identifier, event.type_key))
+            .or_insert(0);
+        *version += 1;
+        event.version = *version;
+    }
+}
+
+/// Determines what a bounded [ModificationChannel](./struct.ModificationChannel.html) does with an event when it is full.
+///
+/// Events that are dropped have been assigned a sequence number and version,
+/// so receivers that check the versions with a [VersionLedger](./struct.VersionLedger.html) notice the gap and can resynchronize.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// Block the sender until there is room in the channel, this is the default.
+    #[default]
+    Block,
+    /// Drop the event that is being sent.
+    DropNewest,
+    /// Drop the oldest event in the channel to make room for the event that is being sent.
+    DropOldest,
+    /// Merge the event into the last pending event with the same identifier and type, which then carries a [batch](./enum.PayloadKind.html#variant.Batch).
+    ///
+    /// The merged event keeps its sequence number and version, the event that is merged into it is not assigned either.
+    /// If there is no such pending event, the oldest event in the channel is dropped to make room, as with `DropOldest`.
+    Coalesce,
+}
 
 /// An event channel over which modification events are sent.
+///
+/// Events that are sent with [send](./struct.ModificationChannel.html#method.send) are assigned a sequence number,
+/// and a version per identifier and type, see [ModificationEvent](./struct.ModificationEvent.html).
+/// Events that are sent directly on the [sender](./struct.ModificationChannel.html#method.sender) are not.
+///
+/// A [bounded](./struct.ModificationChannel.html#method.bounded) channel holds a limited number of events,
+/// its [BackpressurePolicy](./enum.BackpressurePolicy.html) determines what happens when it is full.
 pub struct ModificationChannel<I: Copy + Clone + Send + Sync> {
     event_receiver: Receiver<ModificationEvent<I>>,
     event_sender: Sender<ModificationEvent<I>>,
+    error_policy: ErrorPolicy,
+    backpressure_policy: BackpressurePolicy,
+    sequencer: Mutex<Sequencer<I>>,
+    #[cfg(feature = "async")]
+    wakers: Wakers,
 }
 
 impl<I: Copy + Clone + Send + Sync> ModificationChannel<I> {
@@ -16,9 +108,54 @@ impl<I: Copy + Clone + Send + Sync> ModificationChannel<I> {
         ModificationChannel {
             event_receiver: rx,
             event_sender: tx,
+            error_policy: ErrorPolicy::default(),
+            backpressure_policy: BackpressurePolicy::default(),
+            sequencer: Mutex::new(Sequencer::new()),
+            #[cfg(feature = "async")]
+            wakers: Wakers::default(),
+        }
+    }
+
+    /// Constructs a new modification channel that holds at most `capacity` events.
+    ///
+    /// The [BackpressurePolicy](./enum.BackpressurePolicy.html) determines what happens with events that are sent while the channel is full.
+    /// The policy is only applied to events sent with [send](./struct.ModificationChannel.html#method.send),
+    /// events that are sent directly on the [sender](./struct.ModificationChannel.html#method.sender) block.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0.
+    pub fn bounded(capacity: usize, policy: BackpressurePolicy) -> ModificationChannel<I> {
+        assert!(capacity > 0, "A bounded modification channel needs a capacity of at least 1");
+
+        let (tx, rx) = bounded(capacity);
+
+        ModificationChannel {
+            event_receiver: rx,
+            event_sender: tx,
+            error_policy: ErrorPolicy::default(),
+            backpressure_policy: policy,
+            sequencer: Mutex::new(Sequencer::new()),
+            #[cfg(feature = "async")]
+            wakers: Wakers::default(),
         }
     }
 
+    /// Sets the [ErrorPolicy](./error/enum.ErrorPolicy.html) for trackers created with [Trackable::track_on](./trait.Trackable.html#method.track_on).
+    pub fn with_error_policy(mut self, error_policy: ErrorPolicy) -> ModificationChannel<I> {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Returns the [ErrorPolicy](./error/enum.ErrorPolicy.html) of this channel.
+    pub fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+
+    /// Returns the [BackpressurePolicy](./enum.BackpressurePolicy.html) of this channel.
+    pub fn backpressure_policy(&self) -> BackpressurePolicy {
+        self.backpressure_policy
+    }
+
     /// Returns an sender on which modification events are sent.
     pub fn sender(&self) -> &Sender<ModificationEvent<I>> {
         &self.event_sender
@@ -28,4 +165,189 @@ impl<I: Copy + Clone + Send + Sync> ModificationChannel<I> {
     pub fn receiver(&self) -> &Receiver<ModificationEvent<I>> {
         &self.event_receiver
     }
+
+    #[cfg(feature = "async")]
+    pub(crate) fn wakers(&self) -> &Wakers {
+        &self.wakers
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync> Default for ModificationChannel<I> {
+    fn default() -> Self {
+        ModificationChannel::new()
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> ModificationChannel<I> {
+    /// Assigns the next sequence number and version to the event and sends it.
+    ///
+    /// If the channel is full, the event is handled according to the [BackpressurePolicy](./enum.BackpressurePolicy.html) of the channel.
+    pub fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        let result = self.send_sequenced(event);
+
+        #[cfg(feature = "async")]
+        self.wakers.wake_all();
+
+        result
+    }
+
+    fn send_sequenced(&self, mut event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        // The lock is held while sending to keep the events on the channel in sequence.
+        let mut sequencer = self.sequencer.lock().unwrap_or_else(|e| e.into_inner());
+
+        if self.backpressure_policy == BackpressurePolicy::Coalesce && self.event_sender.is_full() {
+            return self.coalesce(&mut sequencer, event);
+        }
+
+        sequencer.stamp(&mut event);
+
+        match self.backpressure_policy {
+            BackpressurePolicy::Block | BackpressurePolicy::Coalesce => self.send_blocking(event),
+            BackpressurePolicy::DropNewest => match self.event_sender.try_send(event) {
+                Ok(()) | Err(TrySendError::Full(_)) => Ok(()),
+                Err(TrySendError::Disconnected(_)) => Err(ErrorKind::ChannelDisconnected),
+            },
+            BackpressurePolicy::DropOldest => self.send_dropping_oldest(event),
+        }
+    }
+
+    /// Merges the event into the last pending event with the same identifier and type,
+    /// or stamps and sends it, dropping the oldest event, if there is none.
+    ///
+    /// The pending events are taken from the channel and sent again, which never blocks,
+    /// since the lock on the sequencer is held throughout.
+    fn coalesce(
+        &self,
+        sequencer: &mut Sequencer<I>,
+        mut event: ModificationEvent<I>,
+    ) -> Result<(), ErrorKind> {
+        let mut pending = self.event_receiver.try_iter().collect::<Vec<_>>();
+
+        let target = pending.iter_mut().rev().find(|pending| {
+            pending.identifier == event.identifier && pending.type_key == event.type_key
+        });
+
+        let result = match target {
+            Some(target) if target.strategy_id == event.strategy_id => target.coalesce(event),
+            _ => {
+                sequencer.stamp(&mut event);
+                pending.push(event);
+                Ok(())
+            }
+        };
+
+        for pending in pending {
+            self.send_dropping_oldest(pending)?;
+        }
+
+        result
+    }
+
+    fn send_dropping_oldest(&self, mut event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        loop {
+            match self.event_sender.try_send(event) {
+                Ok(()) => return Ok(()),
+                Err(TrySendError::Full(returned)) => {
+                    event = returned;
+                    let _ = self.event_receiver.try_recv();
+                }
+                Err(TrySendError::Disconnected(_)) => return Err(ErrorKind::ChannelDisconnected),
+            }
+        }
+    }
+
+    fn send_blocking(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        self.event_sender
+            .send(event)
+            .map_err(|_| ErrorKind::ChannelDisconnected)
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> ModificationSink<I> for ModificationChannel<I> {
+    fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        ModificationChannel::send(self, event)
+    }
+
+    fn error_policy(&self) -> &ErrorPolicy {
+        &self.error_policy
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use super::{BackpressurePolicy, ModificationChannel};
+    use crate::{
+        event::PayloadKind, fixtures::Position, serialization::bincode::Bincode, Apply, Tracker,
+    };
+
+    fn send_moves(channel: &ModificationChannel<u32>, identifier: u32, xs: &[u32]) {
+        let mut position = Position::default();
+        let mut tracker = Tracker::new(&mut position, channel, Bincode, identifier);
+
+        for x in xs {
+            tracker.x = *x;
+            tracker.commit().unwrap();
+        }
+    }
+
+    #[test]
+    fn full_channel_coalesces_into_a_batch() {
+        let channel = ModificationChannel::bounded(1, BackpressurePolicy::Coalesce);
+
+        send_moves(&channel, 1, &[1, 2, 3]);
+
+        let events = channel.receiver().try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, PayloadKind::Batch);
+        assert_eq!((events[0].sequence, events[0].version), (1, 1));
+
+        let mut position = Position::default();
+        Apply::apply_checked(&mut position, &events[0], Bincode).unwrap();
+        assert_eq!(position.x, 3);
+    }
+
+    #[test]
+    fn full_channel_without_a_pending_event_to_coalesce_into_drops_the_oldest() {
+        let channel = ModificationChannel::bounded(1, BackpressurePolicy::Coalesce);
+
+        send_moves(&channel, 1, &[1]);
+        send_moves(&channel, 2, &[1]);
+
+        let events = channel.receiver().try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].identifier, 2);
+        assert_eq!(events[0].payload, PayloadKind::Diff);
+        assert_eq!((events[0].sequence, events[0].version), (2, 1));
+    }
+
+    #[test]
+    fn full_channel_coalesces_per_identifier() {
+        let channel = ModificationChannel::bounded(2, BackpressurePolicy::Coalesce);
+
+        send_moves(&channel, 1, &[1]);
+        send_moves(&channel, 2, &[1]);
+        send_moves(&channel, 1, &[2]);
+
+        let events = channel.receiver().try_iter().collect::<Vec<_>>();
+        assert_eq!(
+            events
+                .iter()
+                .map(|event| (event.identifier, event.payload))
+                .collect::<Vec<_>>(),
+            vec![(1, PayloadKind::Batch), (2, PayloadKind::Diff)]
+        );
+    }
+
+    #[test]
+    fn full_channel_drops_events() {
+        let newest = ModificationChannel::bounded(1, BackpressurePolicy::DropNewest);
+        send_moves(&newest, 1, &[1, 2]);
+        let versions = newest.receiver().try_iter().map(|event| event.version);
+        assert_eq!(versions.collect::<Vec<_>>(), vec![1]);
+
+        let oldest = ModificationChannel::bounded(1, BackpressurePolicy::DropOldest);
+        send_moves(&oldest, 1, &[1, 2]);
+        let versions = oldest.receiver().try_iter().map(|event| event.version);
+        assert_eq!(versions.collect::<Vec<_>>(), vec![2]);
+    }
 }
@@ -0,0 +1,580 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::Hash,
+    mem,
+};
+
+use crossbeam_channel::Receiver;
+use serde_diff::{Config, Diff};
+
+use crate::{
+    channel::Sequencer, error::ErrorKind, event::PayloadKind,
+    serialization::SerializationStrategy, Apply, FieldPathMode, ModificationEvent,
+    TrackableMarker, TypeKey,
+};
+
+type ApplyFn<I, S> = Box<
+    dyn Fn(&mut (dyn Any + Send), &ModificationEvent<I>, &S) -> Result<(), ErrorKind>
+        + Send
+        + Sync,
+>;
+type LoadFn<S> =
+    Box<dyn Fn(&[u8], &S) -> Result<Box<dyn Any + Send>, ErrorKind> + Send + Sync>;
+type DiffFn<S> = Box<
+    dyn Fn(
+            &(dyn Any + Send),
+            &(dyn Any + Send),
+            FieldPathMode,
+            &S,
+        ) -> Result<Option<Vec<u8>>, ErrorKind>
+        + Send
+        + Sync,
+>;
+type SnapshotFn<S> =
+    Box<dyn Fn(&(dyn Any + Send), &S) -> Result<Vec<u8>, ErrorKind> + Send + Sync>;
+type CopyFn = Box<dyn Fn(&(dyn Any + Send)) -> Box<dyn Any + Send> + Send + Sync>;
+
+/// The functions that merge the events of a registered type.
+struct Merger<I: Copy + Clone + Send + Sync, S> {
+    /// The registered type, which tells it apart from other types with the same type key.
+    type_id: TypeId,
+    apply: ApplyFn<I, S>,
+    load: LoadFn<S>,
+    diff: DiffFn<S>,
+    snapshot: SnapshotFn<S>,
+    copy: CopyFn,
+}
+
+/// The shadow copy of a single identifier and type.
+struct Shadow {
+    /// The value as the receivers know it, after the last drained event.
+    base: Box<dyn Any + Send>,
+    /// The value after the pending events.
+    current: Box<dyn Any + Send>,
+}
+
+/// The pending events of a single identifier and type.
+struct Pending<I: Copy + Clone + Send + Sync> {
+    /// The first event, or the batch of all events if they are not applied to a shadow copy.
+    /// `None` if the events turned out to have no effect.
+    event: Option<ModificationEvent<I>>,
+    /// Whether the events are applied to a shadow copy.
+    shadowed: bool,
+    /// Whether a snapshot is among the events.
+    snapshot: bool,
+}
+
+/// Combines the pending modification events of each identifier and type into a single event.
+///
+/// Place the buffer between a [ModificationChannel](./struct.ModificationChannel.html) and a consumer that runs less often than the events are sent,
+/// such as a network replicator that sends at a fixed tick rate.
+/// Events are added with [push](./struct.CoalescingBuffer.html#method.push) or [pump](./struct.CoalescingBuffer.html#method.pump),
+/// and [drain](./struct.CoalescingBuffer.html#method.drain) returns one event per identifier and type on every tick.
+///
+/// The events of a [registered](./struct.CoalescingBuffer.html#method.register) type are applied to a shadow copy of the instance,
+/// which is then diffed against the value the receivers know, resulting in a single diff.
+/// A shadow copy is created when an instance is [seeded](./struct.CoalescingBuffer.html#method.seed), or when a snapshot of it is pushed.
+/// The events of other types and instances are combined into a [batch](./enum.PayloadKind.html#variant.Batch).
+///
+/// All events must be serialized with the strategy of the buffer.
+/// The drained events are assigned new sequence numbers and versions,
+/// so receivers that check them with a [VersionLedger](./struct.VersionLedger.html) see consecutive versions.
+pub struct CoalescingBuffer<I, S>
+where
+    I: Copy + Clone + Send + Sync + Eq + Hash,
+    S: SerializationStrategy,
+{
+    strategy: S,
+    mergers: HashMap<TypeKey, Merger<I, S>>,
+    shadows: HashMap<(I, TypeKey), Shadow>,
+    pending: Vec<Pending<I>>,
+    pending_index: HashMap<(I, TypeKey), usize>,
+    /// The events that were combined by a drain that failed, they are returned by the next drain.
+    drained: Vec<ModificationEvent<I>>,
+    sequencer: Sequencer<I>,
+}
+
+impl<I, S> CoalescingBuffer<I, S>
+where
+    I: Copy + Clone + Send + Sync + Eq + Hash,
+    S: SerializationStrategy,
+{
+    /// Constructs an empty buffer for events that are serialized with the given strategy.
+    pub fn new(strategy: S) -> CoalescingBuffer<I, S> {
+        CoalescingBuffer {
+            strategy,
+            mergers: HashMap::new(),
+            shadows: HashMap::new(),
+            pending: Vec::new(),
+            pending_index: HashMap::new(),
+            drained: Vec::new(),
+            sequencer: Sequencer::new(),
+        }
+    }
+
+    /// Registers type `C` to be merged on shadow copies.
+    ///
+    /// Returns [ErrorKind::DuplicateTypeKey](./error/enum.ErrorKind.html#variant.DuplicateTypeKey) if the type key of `C` is already registered.
+    pub fn register<C>(&mut self) -> Result<&mut CoalescingBuffer<I, S>, ErrorKind>
+    where
+        C: TrackableMarker,
+        I: 'static,
+        S: 'static,
+    {
+        let apply = |value: &mut (dyn Any + Send), event: &ModificationEvent<I>, strategy: &S| {
+            let value = value
+                .downcast_mut::<C>()
+                .expect("The shadow copy has the registered type.");
+
+            Apply::apply_checked(value, event, strategy.clone())
+        };
+
+        let load = |data: &[u8], strategy: &S| {
+            strategy
+                .deserialize::<C>(data)
+                .map(|value| Box::new(value) as Box<dyn Any + Send>)
+        };
+
+        let diff = |base: &(dyn Any + Send),
+                    current: &(dyn Any + Send),
+                    mode: FieldPathMode,
+                    strategy: &S| {
+            let base = base
+                .downcast_ref::<C>()
+                .expect("The shadow copy has the registered type.");
+            let current = current
+                .downcast_ref::<C>()
+                .expect("The shadow copy has the registered type.");
+
+            let diff = Config::new()
+                .with_field_path_mode(mode.into())
+                .serializable_diff(base, current);
+
+            // `has_changes` is only known after the diff is serialized.
+            let data = strategy.serialize::<Diff<C>>(&diff)?;
+
+            if diff.has_changes() {
+                Ok(Some(data))
+            } else {
+                Ok(None)
+            }
+        };
+
+        let snapshot = |value: &(dyn Any + Send), strategy: &S| {
+            strategy.serialize(
+                value
+                    .downcast_ref::<C>()
+                    .expect("The shadow copy has the registered type."),
+            )
+        };
+
+        let copy = |value: &(dyn Any + Send)| {
+            Box::new(
+                value
+                    .downcast_ref::<C>()
+                    .expect("The shadow copy has the registered type.")
+                    .clone(),
+            ) as Box<dyn Any + Send>
+        };
+
+        let type_key = C::type_key()?;
+        if self.mergers.contains_key(&type_key) {
+            return Err(ErrorKind::DuplicateTypeKey(type_key));
+        }
+
+        self.mergers.insert(
+            type_key,
+            Merger {
+                type_id: TypeId::of::<C>(),
+                apply: Box::new(apply),
+                load: Box::new(load),
+                diff: Box::new(diff),
+                snapshot: Box::new(snapshot),
+                copy: Box::new(copy),
+            },
+        );
+        Ok(self)
+    }
+
+    /// Creates the shadow copy of an instance with the value the receivers know, registering type `C` if needed.
+    ///
+    /// If the instance has pending events, they are replaced by a snapshot of `value`.
+    /// Returns [ErrorKind::DuplicateTypeKey](./error/enum.ErrorKind.html#variant.DuplicateTypeKey) if another type is registered with the type key of `C`.
+    pub fn seed<C>(&mut self, identifier: I, value: &C) -> Result<(), ErrorKind>
+    where
+        C: TrackableMarker,
+        I: 'static,
+        S: 'static,
+    {
+        let type_key = C::type_key()?;
+        match self.mergers.get(&type_key) {
+            Some(merger) if merger.type_id != TypeId::of::<C>() => {
+                return Err(ErrorKind::DuplicateTypeKey(type_key))
+            }
+            Some(_) => {}
+            None => {
+                self.register::<C>()?;
+            }
+        }
+
+        let key = (identifier, type_key);
+        self.shadows.insert(
+            key,
+            Shadow {
+                base: Box::new(value.clone()),
+                current: Box::new(value.clone()),
+            },
+        );
+
+        if let Some(&index) = self.pending_index.get(&key) {
+            let pending = &mut self.pending[index];
+            pending.shadowed = true;
+            pending.snapshot = true;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the shadow copy of an instance, for example after it was destroyed.
+    ///
+    /// The pending events of the instance are combined right away and returned by the next drain.
+    pub fn forget<C: TrackableMarker>(&mut self, identifier: &I) -> Result<(), ErrorKind> {
+        let key = (*identifier, C::type_key()?);
+
+        let mut shadow = match self.shadows.remove(&key) {
+            Some(shadow) => shadow,
+            None => return Ok(()),
+        };
+
+        if let Some(&index) = self.pending_index.get(&key) {
+            let pending = &mut self.pending[index];
+
+            if pending.shadowed {
+                let event = finish(pending, &self.mergers[&key.1], &mut shadow, &self.strategy);
+                pending.shadowed = false;
+                self.pending_index.remove(&key);
+                pending.event = event?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds an event to the buffer.
+    ///
+    /// If the event cannot be applied to the shadow copy of its instance, the error is returned and the event is not added.
+    pub fn push(&mut self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        let key = (event.identifier, event.type_key);
+
+        if let Some(&index) = self.pending_index.get(&key) {
+            let pending = &mut self.pending[index];
+
+            if pending.shadowed {
+                let shadow = self
+                    .shadows
+                    .get_mut(&key)
+                    .expect("Shadowed events have a shadow copy.");
+                (self.mergers[&key.1].apply)(&mut *shadow.current, &event, &self.strategy)?;
+                pending.snapshot |= event.payload == PayloadKind::Snapshot;
+            } else if let Some(pending_event) = &mut pending.event {
+                pending_event.coalesce(event)?;
+            }
+
+            return Ok(());
+        }
+
+        let shadowed = match self.mergers.get(&key.1) {
+            Some(merger) => match (self.shadows.get_mut(&key), event.payload) {
+                (Some(shadow), _) => {
+                    (merger.apply)(&mut *shadow.current, &event, &self.strategy)?;
+                    true
+                }
+                (None, PayloadKind::Snapshot) => {
+                    let current = (merger.load)(&event.modified_fields, &self.strategy)?;
+                    let base = (merger.copy)(&*current);
+                    self.shadows.insert(key, Shadow { base, current });
+                    true
+                }
+                (None, _) => false,
+            },
+            None => false,
+        };
+
+        self.pending_index.insert(key, self.pending.len());
+        self.pending.push(Pending {
+            snapshot: event.payload == PayloadKind::Snapshot,
+            event: Some(event),
+            shadowed,
+        });
+
+        Ok(())
+    }
+
+    /// Adds all events that are waiting on the receiver to the buffer, without blocking.
+    ///
+    /// Returns the number of events that were added.
+    pub fn pump(&mut self, receiver: &Receiver<ModificationEvent<I>>) -> Result<usize, ErrorKind> {
+        let mut count = 0;
+
+        for event in receiver.try_iter() {
+            self.push(event)?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
+    /// Returns the number of identifiers and types with pending events.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len() + self.drained.len()
+    }
+
+    /// Returns the combined event of each identifier and type, in the order their first events were pushed, and clears the buffer.
+    ///
+    /// The events of an instance with a shadow copy are left out if their modifications cancel each other out.
+    /// If the events of an instance cannot be combined, they are discarded together with its shadow copy, and the error is returned.
+    /// The combined events of the other instances are kept, and returned by the next drain.
+    pub fn drain(&mut self) -> Result<Vec<ModificationEvent<I>>, ErrorKind> {
+        self.pending_index.clear();
+
+        let mut events = mem::take(&mut self.drained);
+        let mut error = None;
+
+        for mut pending in mem::take(&mut self.pending) {
+            let event = if pending.shadowed {
+                let key = match &pending.event {
+                    Some(event) => (event.identifier, event.type_key),
+                    None => continue,
+                };
+                let shadow = self
+                    .shadows
+                    .get_mut(&key)
+                    .expect("Shadowed events have a shadow copy.");
+
+                match finish(&mut pending, &self.mergers[&key.1], shadow, &self.strategy) {
+                    Ok(event) => event,
+                    Err(e) => {
+                        self.shadows.remove(&key);
+                        error.get_or_insert(e);
+                        continue;
+                    }
+                }
+            } else {
+                pending.event
+            };
+
+            if let Some(mut event) = event {
+                self.sequencer.stamp(&mut event);
+                events.push(event);
+            }
+        }
+
+        match error {
+            Some(e) => {
+                self.drained = events;
+                Err(e)
+            }
+            None => Ok(events),
+        }
+    }
+}
+
+/// Turns the events that were applied to a shadow copy into a single diff, or a snapshot if a snapshot is among them,
+/// and makes the shadow copy the value the receivers know.
+fn finish<I, S>(
+    pending: &mut Pending<I>,
+    merger: &Merger<I, S>,
+    shadow: &mut Shadow,
+    strategy: &S,
+) -> Result<Option<ModificationEvent<I>>, ErrorKind>
+where
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    let mut event = match pending.event.take() {
+        Some(event) => event,
+        None => return Ok(None),
+    };
+
+    if pending.snapshot {
+        event.modified_fields = (merger.snapshot)(&*shadow.current, strategy)?;
+        event.payload = PayloadKind::Snapshot;
+        event.base_hash = None;
+    } else {
+        // The diff is made in the mode of the first event.
+        match (merger.diff)(&*shadow.base, &*shadow.current, event.field_path_mode, strategy)? {
+            // The base hash of the first event is kept, it was checked against the base value.
+            Some(diff) => {
+                event.modified_fields = diff;
+                event.payload = PayloadKind::Diff;
+            }
+            None => return Ok(None),
+        }
+    }
+
+    event.strategy_id = strategy.strategy_id();
+    shadow.base = (merger.copy)(&*shadow.current);
+
+    Ok(Some(event))
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use serde::{ser, Deserialize, Serialize, Serializer};
+    use serde_diff::SerdeDiff;
+
+    use super::CoalescingBuffer;
+    use crate::{
+        event::PayloadKind,
+        fixtures::{Health, Position},
+        serialization::{bincode::Bincode, SerializationStrategy},
+        Apply, ModificationEvent, TrackableMarker, Tracker,
+    };
+
+    static GAUGE_FAILS: AtomicBool = AtomicBool::new(false);
+
+    /// A value that fails to serialize while `GAUGE_FAILS` is set.
+    #[derive(Clone, Debug, Default, PartialEq, SerdeDiff, Deserialize)]
+    #[serde_diff(opaque)]
+    struct Gauge(u32);
+
+    impl Serialize for Gauge {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if GAUGE_FAILS.load(Ordering::SeqCst) {
+                return Err(ser::Error::custom("gauge fails"));
+            }
+            serializer.serialize_newtype_struct("Gauge", &self.0)
+        }
+    }
+
+    impl TrackableMarker for Gauge {}
+
+    /// Returns the events of applying each modification to `value`.
+    fn modifications<C: TrackableMarker>(
+        identifier: u32,
+        mut value: C,
+        modifications: &[fn(&mut C)],
+    ) -> Vec<ModificationEvent<u32>> {
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut value, Bincode, identifier);
+
+        modifications
+            .iter()
+            .filter_map(|modify| {
+                modify(&mut tracker);
+                tracker.commit().unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shadowed_events_are_merged_into_a_diff() {
+        let mut buffer = CoalescingBuffer::new(Bincode);
+        buffer.seed(1, &Position::default()).unwrap();
+
+        for event in modifications(
+            1,
+            Position::default(),
+            &[|p| p.x = 1, |p| p.y = 2, |p| p.x = 3],
+        ) {
+            buffer.push(event).unwrap();
+        }
+        assert_eq!(buffer.pending_count(), 1);
+
+        let events = buffer.drain().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, PayloadKind::Diff);
+        assert_eq!((events[0].sequence, events[0].version), (1, 1));
+
+        let mut replica = Position::default();
+        Apply::apply_checked(&mut replica, &events[0], Bincode).unwrap();
+        assert_eq!(replica, Position { x: 3, y: 2 });
+        assert_eq!(buffer.pending_count(), 0);
+    }
+
+    #[test]
+    fn cancelled_out_events_are_left_out() {
+        let mut buffer = CoalescingBuffer::new(Bincode);
+        buffer.seed(1, &Position::default()).unwrap();
+
+        for event in modifications(1, Position::default(), &[|p| p.x = 1, |p| p.x = 0]) {
+            buffer.push(event).unwrap();
+        }
+
+        assert!(buffer.drain().unwrap().is_empty());
+    }
+
+    #[test]
+    fn unshadowed_events_are_batched() {
+        let mut buffer = CoalescingBuffer::new(Bincode);
+
+        for event in modifications(1, Health::default(), &[|h| h.value = 1, |h| h.value = 2]) {
+            buffer.push(event).unwrap();
+        }
+
+        let events = buffer.drain().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].payload, PayloadKind::Batch);
+
+        let mut replica = Health::default();
+        Apply::apply_checked(&mut replica, &events[0], Bincode).unwrap();
+        assert_eq!(replica.value, 2);
+    }
+
+    #[test]
+    fn pushed_snapshot_creates_a_shadow_copy() {
+        let mut buffer = CoalescingBuffer::new(Bincode);
+        buffer.register::<Position>().unwrap();
+
+        let snapshot =
+            ModificationEvent::snapshot(&Position { x: 5, y: 5 }, 1u32, &Bincode).unwrap();
+        buffer.push(snapshot).unwrap();
+        for event in modifications(1, Position { x: 5, y: 5 }, &[|p| p.x = 6]) {
+            buffer.push(event).unwrap();
+        }
+
+        let events = buffer.drain().unwrap();
+        let snapshot: Position = Bincode.deserialize(&events[0].modified_fields).unwrap();
+        assert_eq!(events[0].payload, PayloadKind::Snapshot);
+        assert_eq!(snapshot, Position { x: 6, y: 5 });
+
+        for event in modifications(1, Position { x: 6, y: 5 }, &[|p| p.y = 7]) {
+            buffer.push(event).unwrap();
+        }
+        let events = buffer.drain().unwrap();
+        assert_eq!(events[0].payload, PayloadKind::Diff);
+    }
+
+    #[test]
+    fn failed_drain_keeps_the_other_events() {
+        let mut buffer = CoalescingBuffer::new(Bincode);
+        buffer.seed(1, &Gauge(0)).unwrap();
+        buffer.seed(2, &Position::default()).unwrap();
+
+        for event in modifications(1, Gauge(0), &[|g| g.0 = 1]) {
+            buffer.push(event).unwrap();
+        }
+        for event in modifications(2, Position::default(), &[|p| p.x = 1]) {
+            buffer.push(event).unwrap();
+        }
+
+        GAUGE_FAILS.store(true, Ordering::SeqCst);
+        let result = buffer.drain();
+        GAUGE_FAILS.store(false, Ordering::SeqCst);
+
+        assert!(result.is_err());
+        assert_eq!(buffer.pending_count(), 1);
+
+        let events = buffer.drain().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].identifier, 2);
+
+        // The failed instance lost its shadow copy, its events are passed on as they are.
+        for event in modifications(1, Gauge(1), &[|g| g.0 = 2]) {
+            buffer.push(event).unwrap();
+        }
+        assert_eq!(buffer.drain().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,327 @@
+use std::convert::TryFrom;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::ErrorKind, event::PayloadKind, serialization::SerializationStrategy, FieldPathMode,
+    ModificationEvent, TypeKey,
+};
+
+/// The version of the envelope format that is written by [encode](./struct.ModificationEvent.html#method.encode).
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Set if the payload is a snapshot.
+const FLAG_SNAPSHOT: u8 = 0b0000_0001;
+/// Set if the envelope contains a base hash.
+const FLAG_BASE_HASH: u8 = 0b0000_0010;
+/// Set if the payload is a batch.
+const FLAG_BATCH: u8 = 0b0000_0100;
+/// Set if the fields in the payload are identified by name.
+const FLAG_FIELD_NAMES: u8 = 0b0000_1000;
+/// The flags of the current envelope version, a new flag requires a new version.
+const KNOWN_FLAGS: u8 = FLAG_SNAPSHOT | FLAG_BASE_HASH | FLAG_BATCH | FLAG_FIELD_NAMES;
+
+impl<I: Copy + Clone + Send + Sync> ModificationEvent<I> {
+    /// Encodes this event into a self-contained envelope that can be sent to other processes.
+    ///
+    /// The identifier is serialized with the given strategy, the other fields are written as follows, integers in little endian:
+    ///
+    /// | Field | Size |
+    /// | :----- | :----- |
+    /// | format version | 1 byte |
+    /// | flags | 1 byte |
+    /// | [strategy id](./serialization/trait.SerializationStrategy.html#method.strategy_id) of the payload | 1 byte |
+    /// | type key | 8 bytes |
+    /// | sequence | 8 bytes |
+    /// | version | 8 bytes |
+    /// | base hash, if flagged | 8 bytes |
+    /// | identifier length | 4 bytes |
+    /// | identifier | identifier length |
+    /// | payload length | 4 bytes |
+    /// | payload | payload length |
+    ///
+    /// Version 1 defines the flags, from the lowest bit: snapshot, base hash, batch and field names.
+    /// The other bits are reserved, an envelope that sets them is rejected by [decode](./struct.ModificationEvent.html#method.decode).
+    pub fn encode<S: SerializationStrategy>(&self, strategy: &S) -> Result<Vec<u8>, ErrorKind>
+    where
+        I: Serialize,
+    {
+        let identifier = strategy.serialize(&self.identifier)?;
+
+        let mut flags = match self.payload {
+            PayloadKind::Diff => 0,
+            PayloadKind::Snapshot => FLAG_SNAPSHOT,
+            PayloadKind::Batch => FLAG_BATCH,
+        };
+        if self.base_hash.is_some() {
+            flags |= FLAG_BASE_HASH;
+        }
+        if self.field_path_mode == FieldPathMode::Name {
+            flags |= FLAG_FIELD_NAMES;
+        }
+
+        let mut envelope =
+            Vec::with_capacity(43 + identifier.len() + self.modified_fields.len());
+        envelope.push(ENVELOPE_VERSION);
+        envelope.push(flags);
+        envelope.push(self.strategy_id);
+        envelope.extend_from_slice(&self.type_key.value().to_le_bytes());
+        envelope.extend_from_slice(&self.sequence.to_le_bytes());
+        envelope.extend_from_slice(&self.version.to_le_bytes());
+        if let Some(base_hash) = self.base_hash {
+            envelope.extend_from_slice(&base_hash.to_le_bytes());
+        }
+        write_bytes(&mut envelope, &identifier)?;
+        write_bytes(&mut envelope, &self.modified_fields)?;
+
+        Ok(envelope)
+    }
+
+    /// Decodes an event from an envelope that was created with [encode](./struct.ModificationEvent.html#method.encode).
+    ///
+    /// The identifier is deserialized with the given strategy.
+    /// The `type_id` of the decoded event is `None`, use its `type_key` to identify its type.
+    pub fn decode<S: SerializationStrategy>(
+        envelope: &[u8],
+        strategy: &S,
+    ) -> Result<ModificationEvent<I>, ErrorKind>
+    where
+        I: DeserializeOwned,
+    {
+        let mut reader = EnvelopeReader { buffer: envelope };
+
+        let format_version = reader.read_u8()?;
+        if format_version != ENVELOPE_VERSION {
+            return Err(ErrorKind::DeserializationError(
+                format!("Unsupported envelope version: {}", format_version).into(),
+            ));
+        }
+
+        let flags = reader.read_u8()?;
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(ErrorKind::DeserializationError(
+                format!("Unknown envelope flags: {:08b}", flags & !KNOWN_FLAGS).into(),
+            ));
+        }
+        let strategy_id = reader.read_u8()?;
+        let type_key = TypeKey::from_raw(reader.read_u64()?);
+        let sequence = reader.read_u64()?;
+        let version = reader.read_u64()?;
+        let base_hash = if flags & FLAG_BASE_HASH != 0 {
+            Some(reader.read_u64()?)
+        } else {
+            None
+        };
+        let identifier = strategy.deserialize(reader.read_bytes()?)?;
+        let modified_fields = reader.read_bytes()?.to_vec();
+
+        if !reader.buffer.is_empty() {
+            return Err(ErrorKind::DeserializationError(
+                "Envelope contains trailing bytes".into(),
+            ));
+        }
+
+        let payload = match (flags & FLAG_SNAPSHOT != 0, flags & FLAG_BATCH != 0) {
+            (false, false) => PayloadKind::Diff,
+            (true, false) => PayloadKind::Snapshot,
+            (false, true) => PayloadKind::Batch,
+            (true, true) => {
+                return Err(ErrorKind::DeserializationError(
+                    "Envelope is flagged as both snapshot and batch".into(),
+                ))
+            }
+        };
+
+        let field_path_mode = if flags & FLAG_FIELD_NAMES != 0 {
+            FieldPathMode::Name
+        } else {
+            FieldPathMode::Index
+        };
+
+        Ok(ModificationEvent {
+            modified_fields,
+            payload,
+            identifier,
+            type_id: None,
+            type_key,
+            strategy_id,
+            sequence,
+            version,
+            base_hash,
+            field_path_mode,
+        })
+    }
+}
+
+/// Writes the given bytes prefixed with their length.
+fn write_bytes(envelope: &mut Vec<u8>, bytes: &[u8]) -> Result<(), ErrorKind> {
+    let length = u32::try_from(bytes.len()).map_err(|_| {
+        ErrorKind::SerializationError("Envelope field is larger than 4 GiB".into())
+    })?;
+
+    envelope.extend_from_slice(&length.to_le_bytes());
+    envelope.extend_from_slice(bytes);
+
+    Ok(())
+}
+
+/// Reads the fields of an envelope.
+struct EnvelopeReader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> EnvelopeReader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], ErrorKind> {
+        if self.buffer.len() < count {
+            return Err(ErrorKind::DeserializationError(
+                "Envelope is truncated".into(),
+            ));
+        }
+
+        let (taken, rest) = self.buffer.split_at(count);
+        self.buffer = rest;
+
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ErrorKind> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ErrorKind> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ErrorKind> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn read_bytes(&mut self) -> Result<&'a [u8], ErrorKind> {
+        let length = self.read_u32()? as usize;
+        self.take(length)
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::any::TypeId;
+
+    use super::{FLAG_BATCH, FLAG_SNAPSHOT};
+    use crate::{
+        error::ErrorKind, event::PayloadKind, fixtures::Position, serialization::bincode::Bincode,
+        FieldPathMode, ModificationEvent, TypeKey,
+    };
+
+    fn event(
+        payload: PayloadKind,
+        base_hash: Option<u64>,
+        field_path_mode: FieldPathMode,
+    ) -> ModificationEvent<u32> {
+        let mut event = ModificationEvent::new(
+            vec![1, 2, 3],
+            7,
+            TypeId::of::<Position>(),
+            TypeKey::from_raw(42),
+        );
+        event.payload = payload;
+        event.strategy_id = 1;
+        event.sequence = 5;
+        event.version = 3;
+        event.base_hash = base_hash;
+        event.field_path_mode = field_path_mode;
+        event
+    }
+
+    /// Asserts that decoding fails with a deserialization error that mentions `message`.
+    fn assert_decode_error(envelope: &[u8], message: &str) {
+        match ModificationEvent::<u32>::decode(envelope, &Bincode) {
+            Err(ErrorKind::DeserializationError(e)) => {
+                assert!(e.to_string().contains(message), "unexpected error: {}", e)
+            }
+            other => panic!("expected a deserialization error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn round_trip_with_each_flag() {
+        let events = vec![
+            event(PayloadKind::Diff, None, FieldPathMode::Index),
+            event(PayloadKind::Snapshot, None, FieldPathMode::Index),
+            event(PayloadKind::Batch, None, FieldPathMode::Index),
+            event(PayloadKind::Diff, Some(99), FieldPathMode::Index),
+            event(PayloadKind::Diff, None, FieldPathMode::Name),
+        ];
+
+        for event in events {
+            let envelope = event.encode(&Bincode).unwrap();
+            let decoded = ModificationEvent::<u32>::decode(&envelope, &Bincode).unwrap();
+
+            assert_eq!(decoded.modified_fields, event.modified_fields);
+            assert_eq!(decoded.payload, event.payload);
+            assert_eq!(decoded.identifier, event.identifier);
+            assert_eq!(decoded.type_id, None);
+            assert_eq!(decoded.type_key, event.type_key);
+            assert_eq!(decoded.strategy_id, event.strategy_id);
+            assert_eq!(decoded.sequence, event.sequence);
+            assert_eq!(decoded.version, event.version);
+            assert_eq!(decoded.base_hash, event.base_hash);
+            assert_eq!(decoded.field_path_mode, event.field_path_mode);
+        }
+    }
+
+    #[test]
+    fn truncated_envelope_is_rejected() {
+        let envelope = event(PayloadKind::Diff, Some(99), FieldPathMode::Index)
+            .encode(&Bincode)
+            .unwrap();
+
+        for length in 0..envelope.len() {
+            assert!(ModificationEvent::<u32>::decode(&envelope[..length], &Bincode).is_err());
+        }
+        assert_decode_error(&envelope[..envelope.len() - 1], "truncated");
+    }
+
+    #[test]
+    fn unknown_version_is_rejected() {
+        let mut envelope = event(PayloadKind::Diff, None, FieldPathMode::Index)
+            .encode(&Bincode)
+            .unwrap();
+        envelope[0] = 2;
+
+        assert_decode_error(&envelope, "Unsupported envelope version: 2");
+    }
+
+    #[test]
+    fn trailing_bytes_are_rejected() {
+        let mut envelope = event(PayloadKind::Diff, None, FieldPathMode::Index)
+            .encode(&Bincode)
+            .unwrap();
+        envelope.push(0);
+
+        assert_decode_error(&envelope, "trailing bytes");
+    }
+
+    #[test]
+    fn unknown_flags_are_rejected() {
+        let mut envelope = event(PayloadKind::Diff, None, FieldPathMode::Index)
+            .encode(&Bincode)
+            .unwrap();
+        envelope[1] |= 0b1000_0000;
+
+        assert_decode_error(&envelope, "Unknown envelope flags: 10000000");
+    }
+
+    #[test]
+    fn snapshot_batch_conflict_is_rejected() {
+        let mut envelope = event(PayloadKind::Snapshot, None, FieldPathMode::Index)
+            .encode(&Bincode)
+            .unwrap();
+        envelope[1] |= FLAG_SNAPSHOT | FLAG_BATCH;
+
+        assert_decode_error(&envelope, "both snapshot and batch");
+    }
+}
@@ -1,16 +1,198 @@
-use std::fmt::{Display, Formatter};
+use std::{
+    error::Error,
+    fmt::{Debug, Display, Formatter},
+    io,
+};
 
-/// Wrapper for all errors that can occur in `crossterm`.
+use crossbeam_channel::Sender;
+
+use crate::{ModificationEvent, TypeKey};
+
+/// A boxed error that is the source of an [ErrorKind](./enum.ErrorKind.html).
+pub type BoxedError = Box<dyn Error + Send + Sync>;
+
+/// Wrapper for all errors that can occur in `track`.
 #[derive(Debug)]
 pub enum ErrorKind {
-    SerializationError(String),
+    /// A value could not be serialized.
+    SerializationError(BoxedError),
+    /// A value could not be deserialized.
+    DeserializationError(BoxedError),
+    /// Modified values could not be applied to a type.
+    ApplyError(BoxedError),
+    /// The receiving side of a modification channel has been dropped.
+    ChannelDisconnected,
+    /// An event was applied to a different type than it was made for.
+    TypeMismatch {
+        /// The key of the type the event was applied to.
+        expected: TypeKey,
+        /// The key of the type the event was made for.
+        found: TypeKey,
+    },
+    /// The value a modification is applied to differs from the value the modification was made on.
+    BaseMismatch {
+        /// The hash of the value the modification was made on.
+        expected: u64,
+        /// The hash of the value the modification was applied to.
+        found: u64,
+    },
+    /// An I/O error occurred while reading or writing a journal.
+    IoError(io::Error),
+    /// The checksum of received data does not match the data, which means it is corrupted.
+    ChecksumMismatch {
+        /// The checksum that was sent along with the data.
+        expected: u32,
+        /// The checksum of the received data.
+        found: u32,
+    },
+    /// An event was serialized with a different strategy than it is read with.
+    StrategyMismatch {
+        /// The id of the strategy the event is read with.
+        expected: u8,
+        /// The id of the strategy the event was serialized with.
+        found: u8,
+    },
+    /// No [type key](../struct.TypeKey.html) could be derived for a type, it has to implement [TrackableMarker::type_key](../trait.TrackableMarker.html#method.type_key).
+    MissingTypeKey {
+        /// The name of the type, for diagnostics only.
+        type_name: &'static str,
+    },
+    /// A type was registered with a [type key](../struct.TypeKey.html) that is already registered, by the same or by another type.
+    DuplicateTypeKey(TypeKey),
 }
 
 impl Display for ErrorKind {
     fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ErrorKind::SerializationError(e) => {
-                write!(fmt, "Serialization error occurred: {:?}", e)
+                write!(fmt, "Serialization error occurred: {}", e)
+            }
+            ErrorKind::DeserializationError(e) => {
+                write!(fmt, "Deserialization error occurred: {}", e)
+            }
+            ErrorKind::ApplyError(e) => write!(fmt, "Applying modifications failed: {}", e),
+            ErrorKind::ChannelDisconnected => {
+                write!(fmt, "The receiver of the modification channel is disconnected.")
+            }
+            ErrorKind::TypeMismatch { expected, found } => write!(
+                fmt,
+                "Type mismatch, expected type key {} but found {}.",
+                expected, found
+            ),
+            ErrorKind::BaseMismatch { expected, found } => write!(
+                fmt,
+                "Base state mismatch, expected hash {:016x} but found {:016x}.",
+                expected, found
+            ),
+            ErrorKind::IoError(e) => write!(fmt, "I/O error occurred: {}", e),
+            ErrorKind::ChecksumMismatch { expected, found } => write!(
+                fmt,
+                "Checksum mismatch, expected {:08x} but found {:08x}.",
+                expected, found
+            ),
+            ErrorKind::StrategyMismatch { expected, found } => write!(
+                fmt,
+                "Strategy mismatch, expected strategy id {} but found {}.",
+                expected, found
+            ),
+            ErrorKind::MissingTypeKey { type_name } => write!(
+                fmt,
+                "Type `{}` has no serde name, implement `TrackableMarker::type_key` for it.",
+                type_name
+            ),
+            ErrorKind::DuplicateTypeKey(type_key) => {
+                write!(fmt, "Type key {} is already registered.", type_key)
+            }
+        }
+    }
+}
+
+impl Error for ErrorKind {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ErrorKind::SerializationError(e)
+            | ErrorKind::DeserializationError(e)
+            | ErrorKind::ApplyError(e) => Some(&**e),
+            ErrorKind::IoError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ErrorKind {
+    fn from(error: io::Error) -> Self {
+        ErrorKind::IoError(error)
+    }
+}
+
+/// An error that occurs while receiving on a [TypedReceiver](../struct.TypedReceiver.html).
+#[derive(Debug)]
+pub enum TypedRecvError<I: Copy + Clone + Send + Sync> {
+    /// No more events can be received.
+    Disconnected,
+    /// The received event is of another type or strategy, see [TypedModificationEvent::from_event](../struct.TypedModificationEvent.html#method.from_event).
+    ///
+    /// The event is returned along with the error, so that it can still be handled.
+    Rejected(ErrorKind, ModificationEvent<I>),
+}
+
+impl<I: Copy + Clone + Send + Sync> Display for TypedRecvError<I> {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TypedRecvError::Disconnected => {
+                write!(fmt, "The sender of the modification channel is disconnected.")
+            }
+            TypedRecvError::Rejected(e, _) => write!(fmt, "The received event was rejected: {}", e),
+        }
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Debug> Error for TypedRecvError<I> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            TypedRecvError::Disconnected => None,
+            TypedRecvError::Rejected(e, _) => Some(e),
+        }
+    }
+}
+
+/// Determines how a [Tracker](../struct.Tracker.html) deals with errors that occur while it is dropped.
+///
+/// A policy can be set per tracker with [Tracker::with_error_policy](../struct.Tracker.html#method.with_error_policy),
+/// or per channel with [ModificationChannel::with_error_policy](../struct.ModificationChannel.html#method.with_error_policy).
+#[derive(Clone, Debug, Default)]
+pub enum ErrorPolicy {
+    /// Panic with the error, this is the default.
+    ///
+    /// If the thread is already panicking the error is logged instead, to prevent an abort.
+    #[default]
+    Panic,
+    /// Discard the error.
+    Ignore,
+    /// Log the error with the [log](https://crates.io/crates/log) crate.
+    Log,
+    /// Send the error on the given sender.
+    Channel(Sender<ErrorKind>),
+}
+
+impl ErrorPolicy {
+    /// Handles the given error according to this policy.
+    pub(crate) fn handle(&self, error: ErrorKind) {
+        match self {
+            ErrorPolicy::Panic => {
+                if std::thread::panicking() {
+                    log::error!("Could not track modifications because: {}", error);
+                } else {
+                    panic!("Could not track modifications because: {}", error);
+                }
+            }
+            ErrorPolicy::Ignore => {}
+            ErrorPolicy::Log => {
+                log::error!("Could not track modifications because: {}", error);
+            }
+            ErrorPolicy::Channel(sender) => {
+                // If nobody listens for errors there is nobody to report that to either.
+                let _ = sender.send(error);
             }
         }
     }
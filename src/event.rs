@@ -1,23 +1,328 @@
-use std::any::TypeId;
+use std::{any::TypeId, convert::TryFrom};
+
+use crate::{
+    error::ErrorKind, serialization::SerializationStrategy, FieldPathMode, TrackableMarker,
+    TypeKey,
+};
+
+/// The kind of data that is carried by a [ModificationEvent](./struct.ModificationEvent.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PayloadKind {
+    /// The serialized diff of the modified fields.
+    Diff,
+    /// The complete serialized value, which replaces the value it is applied to.
+    Snapshot,
+    /// Multiple diffs and snapshots that are applied in order.
+    ///
+    /// Batches are created when a bounded [ModificationChannel](./struct.ModificationChannel.html) coalesces events,
+    /// see [BackpressurePolicy::Coalesce](./enum.BackpressurePolicy.html#variant.Coalesce).
+    /// Each entry is written as a kind byte, 0 for a diff and 1 for a snapshot, followed by its length as a 4 byte little endian integer and its data.
+    Batch,
+}
 
 #[derive(Clone, Debug)]
 /// A modification event.
+///
+/// It carries either the modified fields of a type, or a snapshot of the whole type, see [PayloadKind](./enum.PayloadKind.html).
 pub struct ModificationEvent<I: Copy + Clone + Send + Sync> {
-    /// The serialized data of the modified structure fields.
+    /// The serialized data of the modified structure fields, or of the whole structure if `payload` is a snapshot.
     pub modified_fields: Vec<u8>,
+    /// The kind of data in `modified_fields`.
+    pub payload: PayloadKind,
     /// The reference to the corresponding type.
     pub identifier: I,
     /// The type id of the component in question.
-    pub type_id: TypeId
+    ///
+    /// This id is only meaningful within the process that sent the event, use `type_key` to identify types across processes.
+    /// It is `None` for events that were [decoded](./struct.ModificationEvent.html#method.decode) or read from a [journal](./journal/index.html).
+    /// To check the type of any event, compare `type_key` with [TypeKey::of](./struct.TypeKey.html#method.of) instead.
+    pub type_id: Option<TypeId>,
+    /// The stable key of the component in question.
+    pub type_key: TypeKey,
+    /// The [id](./serialization/trait.SerializationStrategy.html#method.strategy_id) of the strategy `modified_fields` is serialized with, 0 if unknown.
+    pub strategy_id: u8,
+    /// The position of this event among all events sent on a [ModificationChannel](./struct.ModificationChannel.html), starting at 1.
+    ///
+    /// Is 0 if the event was not sent through a [ModificationChannel](./struct.ModificationChannel.html) or a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html).
+    pub sequence: u64,
+    /// The version of the component after the modification, counted per identifier and type, starting at 1.
+    ///
+    /// Is 0 if the event was not sent through a [ModificationChannel](./struct.ModificationChannel.html) or a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html).
+    pub version: u64,
+    /// The hash of the component before it was modified, if the tracker was configured to send it.
+    ///
+    /// See [Tracker::with_base_hash](./struct.Tracker.html#method.with_base_hash) and [Apply::apply_checked](./struct.Apply.html#method.apply_checked).
+    pub base_hash: Option<u64>,
+    /// The mode in which the fields in the diff are identified.
+    pub field_path_mode: FieldPathMode,
 }
 
 impl<I: Copy + Clone + Send + Sync> ModificationEvent<I> {
     /// Constructs a new [Modification Event](struct.ModificationEvent.html).
-    pub fn new(data: Vec<u8>, identifier: I, type_id: TypeId) -> Self {
+    pub fn new(data: Vec<u8>, identifier: I, type_id: TypeId, type_key: TypeKey) -> Self {
         ModificationEvent {
             modified_fields: data,
+            payload: PayloadKind::Diff,
             identifier,
-            type_id
+            type_id: Some(type_id),
+            type_key,
+            strategy_id: 0,
+            sequence: 0,
+            version: 0,
+            base_hash: None,
+            field_path_mode: FieldPathMode::Index,
+        }
+    }
+
+    /// Constructs an event with a snapshot of the given component.
+    ///
+    /// A snapshot replaces the complete value it is applied to,
+    /// which can be used to bring receivers that joined late or that have diverged up to date.
+    pub fn snapshot<C: TrackableMarker, S: SerializationStrategy>(
+        component: &C,
+        identifier: I,
+        strategy: &S,
+    ) -> Result<Self, ErrorKind> {
+        let mut event = ModificationEvent::new(
+            strategy.serialize(component)?,
+            identifier,
+            TypeId::of::<C>(),
+            C::type_key()?,
+        );
+        event.payload = PayloadKind::Snapshot;
+        event.strategy_id = strategy.strategy_id();
+
+        Ok(event)
+    }
+
+    /// Appends the modifications of `other` to this event, which turns it into a [batch](./enum.PayloadKind.html#variant.Batch).
+    ///
+    /// If `other` is a snapshot, it replaces the modifications of this event instead.
+    /// The sequence number and version of this event are kept, so that `other` does not leave a gap in the versions.
+    pub(crate) fn coalesce(&mut self, other: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        if other.payload == PayloadKind::Snapshot {
+            self.modified_fields = other.modified_fields;
+            self.payload = PayloadKind::Snapshot;
+            self.base_hash = None;
+            return Ok(());
+        }
+
+        if self.payload != PayloadKind::Batch {
+            let mut batch = Vec::with_capacity(
+                10 + self.modified_fields.len() + other.modified_fields.len(),
+            );
+            write_batch_entry(&mut batch, self.payload, &self.modified_fields)?;
+            self.modified_fields = batch;
+            self.payload = PayloadKind::Batch;
+        }
+
+        match other.payload {
+            PayloadKind::Batch => self.modified_fields.extend_from_slice(&other.modified_fields),
+            kind => write_batch_entry(&mut self.modified_fields, kind, &other.modified_fields)?,
+        }
+
+        Ok(())
+    }
+
+    /// Returns the entries of a [batch](./enum.PayloadKind.html#variant.Batch),
+    /// or the payload itself as the only entry if this event is not a batch.
+    pub(crate) fn batch_entries(&self) -> BatchEntries<'_> {
+        match self.payload {
+            PayloadKind::Batch => BatchEntries::Batch(&self.modified_fields),
+            kind => BatchEntries::Single(Some((kind, &self.modified_fields))),
+        }
+    }
+}
+
+/// Appends an entry to the payload of a batch.
+fn write_batch_entry(batch: &mut Vec<u8>, kind: PayloadKind, data: &[u8]) -> Result<(), ErrorKind> {
+    let length = u32::try_from(data.len()).map_err(|_| {
+        ErrorKind::SerializationError("Batch entry is larger than 4 GiB".into())
+    })?;
+
+    batch.push(match kind {
+        PayloadKind::Snapshot => 1,
+        _ => 0,
+    });
+    batch.extend_from_slice(&length.to_le_bytes());
+    batch.extend_from_slice(data);
+
+    Ok(())
+}
+
+/// An iterator over the entries of a payload.
+pub(crate) enum BatchEntries<'a> {
+    Single(Option<(PayloadKind, &'a [u8])>),
+    Batch(&'a [u8]),
+}
+
+impl<'a> Iterator for BatchEntries<'a> {
+    type Item = Result<(PayloadKind, &'a [u8]), ErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            BatchEntries::Single(entry) => entry.take().map(Ok),
+            BatchEntries::Batch(buffer) => {
+                if buffer.is_empty() {
+                    return None;
+                }
+
+                if buffer.len() < 5 {
+                    *buffer = &[];
+                    return Some(Err(ErrorKind::DeserializationError(
+                        "Batch entry is truncated".into(),
+                    )));
+                }
+
+                let kind = match buffer[0] {
+                    0 => PayloadKind::Diff,
+                    1 => PayloadKind::Snapshot,
+                    kind => {
+                        *buffer = &[];
+                        return Some(Err(ErrorKind::DeserializationError(
+                            format!("Unknown batch entry kind: {}", kind).into(),
+                        )));
+                    }
+                };
+                let mut length = [0; 4];
+                length.copy_from_slice(&buffer[1..5]);
+                let length = u32::from_le_bytes(length) as usize;
+
+                if buffer.len() - 5 < length {
+                    *buffer = &[];
+                    return Some(Err(ErrorKind::DeserializationError(
+                        "Batch entry is truncated".into(),
+                    )));
+                }
+
+                let (data, rest) = buffer[5..].split_at(length);
+                *buffer = rest;
+
+                Some(Ok((kind, data)))
+            }
         }
     }
 }
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use super::{ModificationEvent, PayloadKind};
+    use crate::{fixtures::Position, serialization::bincode::Bincode, Apply, Tracker};
+
+    /// Returns the events of setting `x` to each of the given values, starting from the default position.
+    fn moves(xs: &[u32]) -> Vec<ModificationEvent<u32>> {
+        let mut position = Position::default();
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut position, Bincode, 1);
+
+        xs.iter()
+            .map(|x| {
+                tracker.x = *x;
+                tracker.commit().unwrap().unwrap()
+            })
+            .collect()
+    }
+
+    fn entries(event: &ModificationEvent<u32>) -> Vec<(PayloadKind, Vec<u8>)> {
+        event
+            .batch_entries()
+            .map(|entry| entry.map(|(kind, data)| (kind, data.to_vec())).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn coalesced_diffs_form_a_batch() {
+        let events = moves(&[1, 2, 3]);
+        let mut batch = events[0].clone();
+
+        batch.coalesce(events[1].clone()).unwrap();
+        batch.coalesce(events[2].clone()).unwrap();
+
+        assert_eq!(batch.payload, PayloadKind::Batch);
+        assert_eq!(
+            entries(&batch),
+            events
+                .iter()
+                .map(|event| (PayloadKind::Diff, event.modified_fields.clone()))
+                .collect::<Vec<_>>()
+        );
+
+        let mut position = Position::default();
+        Apply::apply_checked(&mut position, &batch, Bincode).unwrap();
+        assert_eq!(position.x, 3);
+    }
+
+    #[test]
+    fn batches_are_flattened() {
+        let events = moves(&[1, 2, 3, 4]);
+        let mut first = events[0].clone();
+        first.coalesce(events[1].clone()).unwrap();
+        let mut second = events[2].clone();
+        second.coalesce(events[3].clone()).unwrap();
+
+        first.coalesce(second).unwrap();
+
+        assert_eq!(entries(&first).len(), 4);
+    }
+
+    #[test]
+    fn snapshot_replaces_the_batch() {
+        let events = moves(&[1, 2]);
+        let snapshot =
+            ModificationEvent::snapshot(&Position { x: 9, y: 9 }, 1u32, &Bincode).unwrap();
+        let mut event = events[0].clone();
+        event.coalesce(events[1].clone()).unwrap();
+
+        event.coalesce(snapshot.clone()).unwrap();
+
+        assert_eq!(event.payload, PayloadKind::Snapshot);
+        assert_eq!(event.modified_fields, snapshot.modified_fields);
+    }
+
+    #[test]
+    fn batch_may_contain_a_snapshot() {
+        let mut snapshot =
+            ModificationEvent::snapshot(&Position { x: 9, y: 9 }, 1u32, &Bincode).unwrap();
+        let events = moves(&[1]);
+        snapshot.coalesce(events[0].clone()).unwrap();
+
+        assert_eq!(snapshot.payload, PayloadKind::Batch);
+        assert_eq!(
+            entries(&snapshot)
+                .into_iter()
+                .map(|(kind, _)| kind)
+                .collect::<Vec<_>>(),
+            vec![PayloadKind::Snapshot, PayloadKind::Diff]
+        );
+
+        let mut position = Position::default();
+        Apply::apply_checked(&mut position, &snapshot, Bincode).unwrap();
+        assert_eq!(position, Position { x: 1, y: 9 });
+    }
+
+    #[test]
+    fn truncated_batch_is_an_error() {
+        let events = moves(&[1, 2]);
+        let mut batch = events[0].clone();
+        batch.coalesce(events[1].clone()).unwrap();
+        batch.modified_fields.pop();
+
+        let mut entries = batch.batch_entries();
+
+        assert!(entries.next().unwrap().is_ok());
+        assert!(entries.next().unwrap().is_err());
+        assert!(entries.next().is_none());
+    }
+
+    #[test]
+    fn unknown_entry_kind_is_an_error() {
+        let events = moves(&[1, 2]);
+        let mut batch = events[0].clone();
+        batch.coalesce(events[1].clone()).unwrap();
+        batch.modified_fields[0] = 2;
+
+        let mut entries = batch.batch_entries();
+
+        assert!(entries.next().unwrap().is_err());
+        assert!(entries.next().is_none());
+    }
+}
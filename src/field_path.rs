@@ -0,0 +1,24 @@
+use serde_diff::FieldPathMode as DiffFieldPathMode;
+
+/// Determines how the fields of structs are identified in a path, such as the path of a diff or of a [FieldChange](./struct.FieldChange.html).
+///
+/// serde-diff tags every path element in a diff, so a diff made in either mode can be applied.
+/// The mode of a diff is recorded in its [ModificationEvent](./struct.ModificationEvent.html#structfield.field_path_mode).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FieldPathMode {
+    /// Fields are identified by their position, which is compact, but changes when fields are added or reordered.
+    /// This is the default.
+    #[default]
+    Index,
+    /// Fields are identified by their name, which is needed when the sender and receiver may run different builds.
+    Name,
+}
+
+impl From<FieldPathMode> for DiffFieldPathMode {
+    fn from(mode: FieldPathMode) -> Self {
+        match mode {
+            FieldPathMode::Index => DiffFieldPathMode::Index,
+            FieldPathMode::Name => DiffFieldPathMode::Name,
+        }
+    }
+}
@@ -0,0 +1,40 @@
+//! Types shared by the unit tests.
+//!
+//! The traits are implemented by hand, so the tests do not depend on the `track` attribute.
+//! Most tests use bincode, so not every fixture is used when it is disabled.
+#![cfg_attr(not(feature = "bincode-serialization"), allow(dead_code))]
+
+#[cfg(feature = "bincode-serialization")]
+use crossbeam_channel::Sender;
+use serde::{Deserialize, Serialize};
+use serde_diff::SerdeDiff;
+
+use crate::TrackableMarker;
+#[cfg(feature = "bincode-serialization")]
+use crate::{serialization::bincode::Bincode, ModificationEvent, Trackable, Tracker};
+
+#[derive(Clone, Debug, Default, PartialEq, SerdeDiff, Serialize, Deserialize)]
+pub(crate) struct Position {
+    pub(crate) x: u32,
+    pub(crate) y: u32,
+}
+
+impl TrackableMarker for Position {}
+
+#[cfg(feature = "bincode-serialization")]
+impl Trackable<Position, Bincode> for Position {
+    fn track<'notifier, I: Copy + Clone + Send + Sync>(
+        &mut self,
+        sender: &'notifier Sender<ModificationEvent<I>>,
+        identifier: I,
+    ) -> Tracker<'_, 'notifier, Position, Bincode, I> {
+        Tracker::new(self, sender, Bincode, identifier)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, SerdeDiff, Serialize, Deserialize)]
+pub(crate) struct Health {
+    pub(crate) value: u32,
+}
+
+impl TrackableMarker for Health {}
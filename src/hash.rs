@@ -0,0 +1,28 @@
+//! Hashing that gives the same results across compilations, platforms and processes.
+
+use serde::Serialize;
+
+use crate::{error::ErrorKind, serialization::SerializationStrategy};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hashes the given bytes with the 64 bit [FNV-1a](http://www.isthe.com/chongo/tech/comp/fnv/) hash function.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Hashes the serialized representation of the given value.
+///
+/// The hash of a value before it was modified is sent along with a modification event,
+/// so the receiver can verify that it applies the modifications to the same value.
+/// This only works if equal values serialize to equal bytes on every peer.
+/// That is not the case for types with a `HashMap` or `HashSet`, whose iteration order differs per instance.
+pub(crate) fn base_hash<C: Serialize, S: SerializationStrategy>(
+    value: &C,
+    strategy: &S,
+) -> Result<u64, ErrorKind> {
+    Ok(fnv1a(&strategy.serialize(value)?))
+}
@@ -0,0 +1,283 @@
+use std::marker::PhantomData;
+
+use serde_diff::{Config, Diff};
+
+use crate::{error::ErrorKind, serialization::SerializationStrategy, TrackableMarker};
+
+/// The forward and reverse diff of a single modification.
+struct UndoEntry<I> {
+    identifier: I,
+    forward: Vec<u8>,
+    reverse: Vec<u8>,
+}
+
+/// A history of modifications that can be undone and redone.
+///
+/// Modifications are recorded as a pair of diffs, one to redo and one to undo the modification.
+/// They can be recorded with [Tracker::commit_to](./struct.Tracker.html#method.commit_to) or [record](./struct.UndoStack.html#method.record).
+/// Each recorded modification is a separate undo step, unless it is recorded between [begin_group](./struct.UndoStack.html#method.begin_group) and [end_group](./struct.UndoStack.html#method.end_group).
+///
+/// Undo and redo look up the values to modify by their identifier in a storage `W`.
+/// When the history is kept for a single value, the lookup can simply return that value: `history.undo(&mut value, |value, _| Some(value))`.
+pub struct UndoStack<C, I, S>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    strategy: S,
+    undo: Vec<Vec<UndoEntry<I>>>,
+    redo: Vec<Vec<UndoEntry<I>>>,
+    group: Option<Vec<UndoEntry<I>>>,
+    phantom: PhantomData<C>,
+}
+
+impl<C, I, S> UndoStack<C, I, S>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    /// Constructs an empty history that serializes the diffs with the given strategy.
+    pub fn new(strategy: S) -> UndoStack<C, I, S> {
+        UndoStack {
+            strategy,
+            undo: Vec::new(),
+            redo: Vec::new(),
+            group: None,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Records the modification of the value with the given identifier from `before` to `after`.
+    ///
+    /// Returns `false`, and records nothing, if the values are equal.
+    /// Recording a modification clears the steps that could be redone.
+    pub fn record(&mut self, identifier: I, before: &C, after: &C) -> Result<bool, ErrorKind> {
+        let forward = match self.serialize_diff(before, after)? {
+            Some(forward) => forward,
+            None => return Ok(false),
+        };
+        let reverse = self.serialize_diff(after, before)?.unwrap_or_default();
+
+        let entry = UndoEntry {
+            identifier,
+            forward,
+            reverse,
+        };
+
+        match &mut self.group {
+            Some(group) => group.push(entry),
+            None => self.undo.push(vec![entry]),
+        }
+        self.redo.clear();
+
+        Ok(true)
+    }
+
+    /// Starts a group, all modifications that are recorded until [end_group](./struct.UndoStack.html#method.end_group) form a single undo step.
+    ///
+    /// Starting a group while a group is open has no effect.
+    pub fn begin_group(&mut self) {
+        if self.group.is_none() {
+            self.group = Some(Vec::new());
+        }
+    }
+
+    /// Ends the current group and adds it as a single undo step, if anything was recorded.
+    pub fn end_group(&mut self) {
+        if let Some(group) = self.group.take() {
+            if !group.is_empty() {
+                self.undo.push(group);
+            }
+        }
+    }
+
+    /// Returns whether there is a step that can be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Returns whether there is a step that can be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Removes all recorded steps.
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+
+    /// Undoes the last step by applying its reverse diffs, in reverse order.
+    ///
+    /// Returns `false` if there is nothing to undo.
+    /// Values that are not found by `lookup` are skipped.
+    /// If applying a diff fails, the values that were already modified are restored and the step can be undone again.
+    pub fn undo<W, L>(&mut self, storage: &mut W, mut lookup: L) -> Result<bool, ErrorKind>
+    where
+        L: for<'w> FnMut(&'w mut W, &I) -> Option<&'w mut C>,
+    {
+        let step = match self.undo.pop() {
+            Some(step) => step,
+            None => return Ok(false),
+        };
+
+        let entries = step
+            .iter()
+            .rev()
+            .map(|entry| (entry.identifier, &entry.reverse, &entry.forward));
+
+        if let Err(e) = self.apply_step(storage, &mut lookup, entries) {
+            self.undo.push(step);
+            return Err(e);
+        }
+
+        self.redo.push(step);
+
+        Ok(true)
+    }
+
+    /// Redoes the last undone step by applying its forward diffs.
+    ///
+    /// Returns `false` if there is nothing to redo.
+    /// Values that are not found by `lookup` are skipped.
+    /// If applying a diff fails, the values that were already modified are restored and the step can be redone again.
+    pub fn redo<W, L>(&mut self, storage: &mut W, mut lookup: L) -> Result<bool, ErrorKind>
+    where
+        L: for<'w> FnMut(&'w mut W, &I) -> Option<&'w mut C>,
+    {
+        let step = match self.redo.pop() {
+            Some(step) => step,
+            None => return Ok(false),
+        };
+
+        let entries = step
+            .iter()
+            .map(|entry| (entry.identifier, &entry.forward, &entry.reverse));
+
+        if let Err(e) = self.apply_step(storage, &mut lookup, entries) {
+            self.redo.push(step);
+            return Err(e);
+        }
+
+        self.undo.push(step);
+
+        Ok(true)
+    }
+
+    /// Applies the diffs of a step in the given order, each given with the diff that reverts it.
+    ///
+    /// If a diff fails, the diffs before it are reverted, so either the whole step is applied or nothing is.
+    fn apply_step<'s, W, L, E>(
+        &self,
+        storage: &mut W,
+        lookup: &mut L,
+        entries: E,
+    ) -> Result<(), ErrorKind>
+    where
+        L: for<'w> FnMut(&'w mut W, &I) -> Option<&'w mut C>,
+        E: Iterator<Item = (I, &'s Vec<u8>, &'s Vec<u8>)>,
+    {
+        let mut applied = Vec::new();
+
+        for (identifier, diff, revert) in entries {
+            if let Some(component) = lookup(storage, &identifier) {
+                if let Err(e) = self.apply_entry(component, diff) {
+                    for (identifier, revert) in applied.into_iter().rev() {
+                        if let Some(component) = lookup(storage, &identifier) {
+                            // The revert is the exact inverse of a diff that was just applied.
+                            let _ = self.apply_entry(component, revert);
+                        }
+                    }
+                    return Err(e);
+                }
+                applied.push((identifier, revert));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies a single diff, the component is left untouched if it fails.
+    fn apply_entry(&self, component: &mut C, diff: &[u8]) -> Result<(), ErrorKind> {
+        let mut copy = component.clone();
+        self.strategy.apply_to(&mut copy, diff)?;
+        *component = copy;
+
+        Ok(())
+    }
+
+    /// Serializes the diff from `old` to `new`, or returns `None` if there are no changes.
+    fn serialize_diff(&self, old: &C, new: &C) -> Result<Option<Vec<u8>>, ErrorKind> {
+        let diff = Config::new()
+            .with_field_path_mode(C::field_path_mode().into())
+            .serializable_diff(old, new);
+
+        // `has_changes` is only known after the diff is serialized.
+        let data = self.strategy.serialize::<Diff<C>>(&diff)?;
+
+        if diff.has_changes() {
+            Ok(Some(data))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use super::UndoStack;
+    use crate::{fixtures::Position, serialization::bincode::Bincode};
+
+    fn lookup<'w>(storage: &'w mut [Position; 2], identifier: &usize) -> Option<&'w mut Position> {
+        storage.get_mut(*identifier)
+    }
+
+    #[test]
+    fn undo_and_redo_a_group() {
+        let mut history = UndoStack::<Position, usize, Bincode>::new(Bincode);
+        let before = [Position { x: 1, y: 1 }, Position { x: 2, y: 2 }];
+        let after = [Position { x: 5, y: 1 }, Position { x: 2, y: 6 }];
+
+        history.begin_group();
+        history.record(0, &before[0], &after[0]).unwrap();
+        history.record(1, &before[1], &after[1]).unwrap();
+        history.end_group();
+
+        let mut storage = after.clone();
+        assert!(history.undo(&mut storage, lookup).unwrap());
+        assert_eq!(storage, before);
+        assert!(!history.can_undo());
+
+        assert!(history.redo(&mut storage, lookup).unwrap());
+        assert_eq!(storage, after);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn failed_undo_restores_values_and_keeps_the_step() {
+        let mut history = UndoStack::<Position, usize, Bincode>::new(Bincode);
+        let before = [Position { x: 1, y: 1 }, Position { x: 2, y: 2 }];
+        let after = [Position { x: 5, y: 1 }, Position { x: 2, y: 6 }];
+
+        history.begin_group();
+        history.record(0, &before[0], &after[0]).unwrap();
+        history.record(1, &before[1], &after[1]).unwrap();
+        history.end_group();
+
+        // Entries are undone in reverse order, so the first entry fails after the second is applied.
+        let reverse = std::mem::replace(&mut history.undo[0][0].reverse, vec![0xff; 4]);
+
+        let mut storage = after.clone();
+        assert!(history.undo(&mut storage, lookup).is_err());
+        assert_eq!(storage, after);
+        assert!(history.can_undo());
+        assert!(!history.can_redo());
+
+        history.undo[0][0].reverse = reverse;
+        assert!(history.undo(&mut storage, lookup).unwrap());
+        assert_eq!(storage, before);
+    }
+}
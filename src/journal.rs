@@ -0,0 +1,712 @@
+//! An append-only journal of modification events.
+//!
+//! A [JournalWriter](./struct.JournalWriter.html) appends events to a writer, such as a file, each prefixed with its length.
+//! A [JournalReader](./struct.JournalReader.html) reads the events back,
+//! and can [replay](./struct.JournalReader.html#method.replay) them through an [ApplyRegistry](../struct.ApplyRegistry.html) to rebuild the state.
+//!
+//! Each record holds the time it was written followed by the event [encoded](../struct.ModificationEvent.html#method.encode) in an envelope.
+//! The identifiers are serialized with a [SerializationStrategy](../serialization/trait.SerializationStrategy.html),
+//! the reader must use the same strategy as the writer.
+//!
+//! A journal grows with every event, a [Compactor](./struct.Compactor.html) folds its events into snapshots to keep the replay time bounded.
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    hash::Hash,
+    io::{self, Read, Write},
+    marker::PhantomData,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crossbeam_channel::Receiver;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    error::ErrorKind, event::PayloadKind, serialization::SerializationStrategy, Apply,
+    ApplyRegistry, FieldPathMode, ModificationEvent, TrackableMarker, TypeKey,
+};
+
+/// The size of the recording time that precedes the envelope in a record.
+const RECORDED_AT_SIZE: usize = 8;
+
+/// The largest record a [JournalReader](./struct.JournalReader.html) reads by default, 64 MiB.
+pub const DEFAULT_MAX_RECORD_SIZE: usize = 64 * 1024 * 1024;
+
+/// An event that was read from a journal.
+#[derive(Clone, Debug)]
+pub struct JournalEntry<I: Copy + Clone + Send + Sync> {
+    /// The event, its `type_id` is `None` because type ids are not stored.
+    pub event: ModificationEvent<I>,
+    /// The time at which the event was written to the journal, with millisecond precision.
+    pub recorded_at: SystemTime,
+}
+
+/// Limits a [replay](./struct.JournalReader.html#method.replay) to the events up to, and including, a certain point.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReplayCutoff {
+    /// Replay the events up to this sequence number.
+    /// Events that were not sent through a [ModificationChannel](../struct.ModificationChannel.html) have no sequence number and are always replayed.
+    Sequence(u64),
+    /// Replay the events that were recorded up to this time.
+    Time(SystemTime),
+}
+
+impl ReplayCutoff {
+    /// Returns whether the given entry lies within this cutoff.
+    fn includes<I: Copy + Clone + Send + Sync>(&self, entry: &JournalEntry<I>) -> bool {
+        match *self {
+            ReplayCutoff::Sequence(sequence) => entry.event.sequence <= sequence,
+            ReplayCutoff::Time(time) => entry.recorded_at <= time,
+        }
+    }
+}
+
+/// Appends modification events to a journal.
+pub struct JournalWriter<W: Write, S: SerializationStrategy> {
+    writer: W,
+    strategy: S,
+}
+
+impl<W: Write, S: SerializationStrategy> JournalWriter<W, S> {
+    /// Constructs a journal writer that appends to the given writer.
+    ///
+    /// The writer is not buffered, wrap it in a [BufWriter](https://doc.rust-lang.org/std/io/struct.BufWriter.html) when writing to a file.
+    pub fn new(writer: W, strategy: S) -> JournalWriter<W, S> {
+        JournalWriter { writer, strategy }
+    }
+
+    /// Appends the given event to the journal.
+    pub fn write<I>(&mut self, event: &ModificationEvent<I>) -> Result<(), ErrorKind>
+    where
+        I: Copy + Clone + Send + Sync + Serialize,
+    {
+        self.write_at(event, SystemTime::now())
+    }
+
+    /// Appends all events that are currently pending on the given receiver to the journal.
+    ///
+    /// The events are taken from the receiver, so they will not be received by others.
+    /// Returns the number of written events.
+    pub fn write_pending<I>(
+        &mut self,
+        receiver: &Receiver<ModificationEvent<I>>,
+    ) -> Result<usize, ErrorKind>
+    where
+        I: Copy + Clone + Send + Sync + Serialize,
+    {
+        let mut written = 0;
+
+        for event in receiver.try_iter() {
+            self.write(&event)?;
+            written += 1;
+        }
+
+        Ok(written)
+    }
+
+    /// Flushes the underlying writer.
+    pub fn flush(&mut self) -> Result<(), ErrorKind> {
+        Ok(self.writer.flush()?)
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Appends the given event to the journal with the given recording time.
+    pub(crate) fn write_at<I>(
+        &mut self,
+        event: &ModificationEvent<I>,
+        recorded_at: SystemTime,
+    ) -> Result<(), ErrorKind>
+    where
+        I: Copy + Clone + Send + Sync + Serialize,
+    {
+        let recorded_at = recorded_at
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+
+        let envelope = event.encode(&self.strategy)?;
+        let length = RECORDED_AT_SIZE + envelope.len();
+
+        if length > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "journal record is larger than 4 GiB",
+            )
+            .into());
+        }
+
+        self.writer.write_all(&(length as u32).to_le_bytes())?;
+        self.writer.write_all(&recorded_at.to_le_bytes())?;
+        self.writer.write_all(&envelope)?;
+
+        Ok(())
+    }
+}
+
+/// Reads modification events from a journal.
+///
+/// The reader is an iterator over the [entries](./struct.JournalEntry.html) in the journal.
+/// It stops after the first error.
+pub struct JournalReader<R: Read, S: SerializationStrategy, I> {
+    reader: R,
+    strategy: S,
+    max_record_size: usize,
+    failed: bool,
+    phantom: PhantomData<I>,
+}
+
+impl<R, S, I> JournalReader<R, S, I>
+where
+    R: Read,
+    S: SerializationStrategy,
+    I: Copy + Clone + Send + Sync + DeserializeOwned,
+{
+    /// Constructs a journal reader that reads from the given reader.
+    pub fn new(reader: R, strategy: S) -> JournalReader<R, S, I> {
+        JournalReader {
+            reader,
+            strategy,
+            max_record_size: DEFAULT_MAX_RECORD_SIZE,
+            failed: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Sets the size of the largest record that is read, which defaults to [DEFAULT_MAX_RECORD_SIZE](./constant.DEFAULT_MAX_RECORD_SIZE.html).
+    ///
+    /// The length of a record is read before the record itself, a corrupt journal could otherwise make the reader allocate up to 4 GiB.
+    /// A record that is larger than this limit is reported as an error.
+    pub fn with_max_record_size(mut self, max_record_size: usize) -> JournalReader<R, S, I> {
+        self.max_record_size = max_record_size;
+        self
+    }
+
+    /// Applies the events in the journal to the given storage, in the order they were written.
+    ///
+    /// If a cutoff is given, the replay stops at the first event beyond it.
+    /// Returns the number of events that were applied.
+    pub fn replay<A, W>(
+        self,
+        registry: &ApplyRegistry<I, A, W>,
+        storage: &mut W,
+        cutoff: Option<ReplayCutoff>,
+    ) -> Result<usize, ErrorKind>
+    where
+        A: SerializationStrategy,
+    {
+        let mut applied = 0;
+
+        for entry in self {
+            let entry = entry?;
+
+            if let Some(cutoff) = cutoff {
+                if !cutoff.includes(&entry) {
+                    break;
+                }
+            }
+
+            if registry.apply(storage, &entry.event)? {
+                applied += 1;
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Reads the next entry, or returns `None` at the end of the journal.
+    fn read_entry(&mut self) -> Result<Option<JournalEntry<I>>, ErrorKind> {
+        let mut length = [0u8; 4];
+        let mut read = 0;
+
+        while read < length.len() {
+            match self.reader.read(&mut length[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        if read == 0 {
+            return Ok(None);
+        } else if read < length.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "journal ends within a record length",
+            )
+            .into());
+        }
+
+        let length = u32::from_le_bytes(length) as usize;
+
+        if length > self.max_record_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "journal record is larger than the maximum record size",
+            )
+            .into());
+        }
+
+        let mut data = vec![0; length];
+        self.reader.read_exact(&mut data)?;
+
+        if data.len() < RECORDED_AT_SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "journal record is shorter than its recording time",
+            )
+            .into());
+        }
+
+        let (recorded_at, envelope) = data.split_at(RECORDED_AT_SIZE);
+        let mut recorded_at_bytes = [0; RECORDED_AT_SIZE];
+        recorded_at_bytes.copy_from_slice(recorded_at);
+        let recorded_at = Duration::from_millis(u64::from_le_bytes(recorded_at_bytes));
+
+        Ok(Some(JournalEntry {
+            event: ModificationEvent::decode(envelope, &self.strategy)?,
+            recorded_at: UNIX_EPOCH + recorded_at,
+        }))
+    }
+}
+
+impl<R, S, I> Iterator for JournalReader<R, S, I>
+where
+    R: Read,
+    S: SerializationStrategy,
+    I: Copy + Clone + Send + Sync + DeserializeOwned,
+{
+    type Item = Result<JournalEntry<I>, ErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        match self.read_entry() {
+            Ok(entry) => entry.map(Ok),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+type FoldFn<I, S> = Box<
+    dyn Fn(&mut Option<Box<dyn Any + Send>>, &ModificationEvent<I>, &S) -> Result<(), ErrorKind>
+        + Send
+        + Sync,
+>;
+type SnapshotFn<S> = Box<dyn Fn(&dyn Any, &S) -> Result<Vec<u8>, ErrorKind> + Send + Sync>;
+
+/// The functions that fold the events of a registered type.
+struct Folder<I: Copy + Clone + Send + Sync, S> {
+    fold: FoldFn<I, S>,
+    snapshot: SnapshotFn<S>,
+}
+
+/// The folded state of a single identifier and type.
+struct FoldedState<I: Copy + Clone + Send + Sync> {
+    state: Option<Box<dyn Any + Send>>,
+    last: JournalEntry<I>,
+}
+
+/// Compacts a journal by folding all events per identifier and type into a single snapshot.
+///
+/// The compacted journal starts with these snapshots, followed by the events after the compaction cutoff.
+/// Replaying the compacted journal results in the same state as replaying the original journal.
+///
+/// The events of each type are folded with [Apply::apply_checked](../struct.Apply.html#method.apply_checked),
+/// starting from the initial value the type was [registered](./struct.Compactor.html#method.register) with.
+/// Events of types that are not registered are kept as they are.
+pub struct Compactor<I, S>
+where
+    I: Copy + Clone + Send + Sync + Eq + Hash,
+    S: SerializationStrategy,
+{
+    strategy: S,
+    folders: HashMap<TypeKey, Folder<I, S>>,
+}
+
+impl<I, S> Compactor<I, S>
+where
+    I: Copy + Clone + Send + Sync + Eq + Hash,
+    S: SerializationStrategy,
+{
+    /// Constructs a compactor for events that were serialized with the given strategy.
+    pub fn new(strategy: S) -> Compactor<I, S> {
+        Compactor {
+            strategy,
+            folders: HashMap::new(),
+        }
+    }
+
+    /// Registers type `C` to be folded into snapshots.
+    ///
+    /// `initial` returns the value of an identifier before its first event,
+    /// this should be the value the receivers start with, such as a default value.
+    /// Returns [ErrorKind::DuplicateTypeKey](../error/enum.ErrorKind.html#variant.DuplicateTypeKey) if the type key of `C` is already registered.
+    pub fn register<C, F>(&mut self, initial: F) -> Result<&mut Compactor<I, S>, ErrorKind>
+    where
+        C: TrackableMarker,
+        F: Fn(&I) -> C + Send + Sync + 'static,
+        I: 'static,
+        S: 'static,
+    {
+        let fold = move |state: &mut Option<Box<dyn Any + Send>>,
+                         event: &ModificationEvent<I>,
+                         strategy: &S| {
+            let component = state
+                .get_or_insert_with(|| {
+                    Box::new(initial(&event.identifier)) as Box<dyn Any + Send>
+                })
+                .downcast_mut::<C>()
+                .expect("The folded state has the registered type.");
+
+            Apply::apply_checked(component, event, strategy.clone())
+        };
+
+        let snapshot = |state: &dyn Any, strategy: &S| {
+            strategy.serialize(
+                state
+                    .downcast_ref::<C>()
+                    .expect("The folded state has the registered type."),
+            )
+        };
+
+        let type_key = C::type_key()?;
+        if self.folders.contains_key(&type_key) {
+            return Err(ErrorKind::DuplicateTypeKey(type_key));
+        }
+
+        self.folders.insert(
+            type_key,
+            Folder {
+                fold: Box::new(fold),
+                snapshot: Box::new(snapshot),
+            },
+        );
+        Ok(self)
+    }
+
+    /// Reads the journal and writes the compacted journal to the given writer.
+    ///
+    /// The events up to the cutoff, or all events if there is no cutoff, are folded into snapshots.
+    /// Each snapshot takes over the sequence number, version and recording time of the last event it contains.
+    /// Returns the number of events that were folded.
+    pub fn compact<R, RS, W, WS>(
+        &self,
+        mut reader: JournalReader<R, RS, I>,
+        writer: &mut JournalWriter<W, WS>,
+        cutoff: Option<ReplayCutoff>,
+    ) -> Result<usize, ErrorKind>
+    where
+        R: Read,
+        RS: SerializationStrategy,
+        W: Write,
+        WS: SerializationStrategy,
+        I: Serialize + DeserializeOwned,
+    {
+        let mut order = Vec::new();
+        let mut folded: HashMap<(I, TypeKey), FoldedState<I>> = HashMap::new();
+        let mut folded_count = 0;
+
+        for entry in reader.by_ref() {
+            let entry = entry?;
+
+            if let Some(cutoff) = cutoff {
+                if !cutoff.includes(&entry) {
+                    self.write_snapshots(&order, &mut folded, writer)?;
+                    writer.write_at(&entry.event, entry.recorded_at)?;
+                    break;
+                }
+            }
+
+            let folder = match self.folders.get(&entry.event.type_key) {
+                Some(folder) => folder,
+                None => {
+                    writer.write_at(&entry.event, entry.recorded_at)?;
+                    continue;
+                }
+            };
+
+            let key = (entry.event.identifier, entry.event.type_key);
+            let folded_state = folded.entry(key).or_insert_with(|| {
+                order.push(key);
+                FoldedState {
+                    state: None,
+                    last: entry.clone(),
+                }
+            });
+
+            (folder.fold)(&mut folded_state.state, &entry.event, &self.strategy)?;
+            folded_state.last = entry;
+            folded_count += 1;
+        }
+
+        // Without cutoff, or if no event lies beyond it, the snapshots are written at the end.
+        self.write_snapshots(&order, &mut folded, writer)?;
+
+        for entry in reader {
+            let entry = entry?;
+            writer.write_at(&entry.event, entry.recorded_at)?;
+        }
+
+        Ok(folded_count)
+    }
+
+    /// Writes a snapshot for each folded state, in the order the identifiers first appeared, and clears them.
+    fn write_snapshots<W, WS>(
+        &self,
+        order: &[(I, TypeKey)],
+        folded: &mut HashMap<(I, TypeKey), FoldedState<I>>,
+        writer: &mut JournalWriter<W, WS>,
+    ) -> Result<(), ErrorKind>
+    where
+        W: Write,
+        WS: SerializationStrategy,
+        I: Serialize,
+    {
+        for key in order {
+            let folded_state = match folded.remove(key) {
+                Some(folded_state) => folded_state,
+                None => continue,
+            };
+            let state = match &folded_state.state {
+                Some(state) => state,
+                None => continue,
+            };
+            let folder = &self.folders[&key.1];
+
+            let last = folded_state.last.event;
+            let snapshot = ModificationEvent {
+                modified_fields: (folder.snapshot)(&**state, &self.strategy)?,
+                payload: PayloadKind::Snapshot,
+                identifier: last.identifier,
+                type_id: None,
+                type_key: last.type_key,
+                strategy_id: self.strategy.strategy_id(),
+                sequence: last.sequence,
+                version: last.version,
+                base_hash: None,
+                field_path_mode: FieldPathMode::Index,
+            };
+
+            writer.write_at(&snapshot, folded_state.last.recorded_at)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::{
+        io::Cursor,
+        time::{Duration, UNIX_EPOCH},
+    };
+
+    use super::{Compactor, JournalReader, JournalWriter, ReplayCutoff};
+    use crate::{
+        error::ErrorKind,
+        event::PayloadKind,
+        fixtures::{Health, Position},
+        serialization::bincode::Bincode,
+        ApplyRegistry, ModificationEvent, Tracker,
+    };
+
+    /// Returns the events of moving position `identifier` to each of the given x values, numbered from 1.
+    fn moves(identifier: u32, xs: &[u32]) -> Vec<ModificationEvent<u32>> {
+        let mut position = Position::default();
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut position, Bincode, identifier);
+
+        xs.iter()
+            .enumerate()
+            .map(|(index, x)| {
+                tracker.x = *x;
+                let mut event = tracker.commit().unwrap().unwrap();
+                event.sequence = index as u64 + 1;
+                event
+            })
+            .collect()
+    }
+
+    fn journal(events: &[ModificationEvent<u32>]) -> Vec<u8> {
+        let mut writer = JournalWriter::new(Vec::new(), Bincode);
+
+        for (index, event) in events.iter().enumerate() {
+            writer
+                .write_at(event, UNIX_EPOCH + Duration::from_secs(index as u64))
+                .unwrap();
+        }
+
+        writer.into_inner()
+    }
+
+    fn registry() -> ApplyRegistry<u32, Bincode, Vec<Position>> {
+        let mut registry = ApplyRegistry::new(Bincode);
+        registry
+            .register::<Position, _>(|storage: &mut Vec<Position>, identifier: &u32| {
+                storage.get_mut(*identifier as usize)
+            })
+            .unwrap();
+        registry
+    }
+
+    #[test]
+    fn entries_are_read_back_in_order() {
+        let events = moves(1, &[1, 2, 3]);
+        let data = journal(&events);
+
+        let entries = JournalReader::<_, _, u32>::new(Cursor::new(data), Bincode)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(entries.len(), 3);
+        for (index, (entry, event)) in entries.iter().zip(&events).enumerate() {
+            assert_eq!(entry.event.identifier, 1);
+            assert_eq!(entry.event.sequence, event.sequence);
+            assert_eq!(entry.event.modified_fields, event.modified_fields);
+            assert_eq!(entry.event.type_id, None);
+            assert_eq!(
+                entry.recorded_at,
+                UNIX_EPOCH + Duration::from_secs(index as u64)
+            );
+        }
+    }
+
+    #[test]
+    fn replay_stops_at_the_cutoff() {
+        let data = journal(&moves(1, &[1, 2, 3]));
+        let mut storage = vec![Position::default(); 2];
+
+        let applied = JournalReader::new(Cursor::new(data.clone()), Bincode)
+            .replay(&registry(), &mut storage, Some(ReplayCutoff::Sequence(2)))
+            .unwrap();
+        assert_eq!(applied, 2);
+        assert_eq!(storage[1].x, 2);
+
+        let applied = JournalReader::new(Cursor::new(data), Bincode)
+            .replay(&registry(), &mut storage, None)
+            .unwrap();
+        assert_eq!(applied, 3);
+        assert_eq!(storage[1].x, 3);
+    }
+
+    #[test]
+    fn truncated_record_is_an_error() {
+        let mut data = journal(&moves(1, &[1, 2]));
+        data.truncate(data.len() - 1);
+
+        let mut reader = JournalReader::<_, _, u32>::new(Cursor::new(data), Bincode);
+
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn oversized_record_is_rejected() {
+        let data = u32::MAX.to_le_bytes().to_vec();
+
+        let mut reader = JournalReader::<_, _, u32>::new(Cursor::new(data), Bincode);
+
+        match reader.next() {
+            Some(Err(ErrorKind::IoError(e))) => {
+                assert_eq!(e.kind(), std::io::ErrorKind::InvalidData)
+            }
+            other => panic!("expected an invalid data error, got {:?}", other),
+        }
+    }
+
+    fn compact(data: Vec<u8>, cutoff: Option<ReplayCutoff>) -> (usize, Vec<u8>) {
+        let mut compactor = Compactor::<u32, _>::new(Bincode);
+        compactor
+            .register::<Position, _>(|_| Position::default())
+            .unwrap();
+
+        let mut writer = JournalWriter::new(Vec::new(), Bincode);
+        let folded = compactor
+            .compact(
+                JournalReader::new(Cursor::new(data), Bincode),
+                &mut writer,
+                cutoff,
+            )
+            .unwrap();
+
+        (folded, writer.into_inner())
+    }
+
+    fn payloads(data: Vec<u8>) -> Vec<(u32, PayloadKind, u64)> {
+        JournalReader::<_, _, u32>::new(Cursor::new(data), Bincode)
+            .map(|entry| {
+                let event = entry.unwrap().event;
+                (event.identifier, event.payload, event.sequence)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn compaction_folds_events_into_snapshots() {
+        let mut events = moves(0, &[1, 2, 3]);
+        events.extend(moves(1, &[4, 5]));
+        let data = journal(&events);
+
+        let (folded, compacted) = compact(data.clone(), None);
+
+        assert_eq!(folded, 5);
+        assert_eq!(
+            payloads(compacted.clone()),
+            vec![(0, PayloadKind::Snapshot, 3), (1, PayloadKind::Snapshot, 2)]
+        );
+
+        let mut original = vec![Position::default(); 2];
+        JournalReader::new(Cursor::new(data), Bincode)
+            .replay(&registry(), &mut original, None)
+            .unwrap();
+        let mut replayed = vec![Position::default(); 2];
+        JournalReader::new(Cursor::new(compacted), Bincode)
+            .replay(&registry(), &mut replayed, None)
+            .unwrap();
+
+        assert_eq!(replayed, original);
+        assert_eq!(replayed[0].x, 3);
+        assert_eq!(replayed[1].x, 5);
+    }
+
+    #[test]
+    fn compaction_keeps_the_events_after_the_cutoff() {
+        let data = journal(&moves(0, &[1, 2, 3]));
+
+        let (folded, compacted) = compact(data, Some(ReplayCutoff::Sequence(2)));
+
+        assert_eq!(folded, 2);
+        assert_eq!(
+            payloads(compacted),
+            vec![(0, PayloadKind::Snapshot, 2), (0, PayloadKind::Diff, 3)]
+        );
+    }
+
+    #[test]
+    fn compaction_keeps_unregistered_types() {
+        let mut health = Health::default();
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut health, Bincode, 0);
+        tracker.value = 10;
+        let mut events = vec![tracker.finish().unwrap()];
+        events.extend(moves(0, &[1, 2]));
+
+        let (folded, compacted) = compact(journal(&events), None);
+
+        assert_eq!(folded, 2);
+        assert_eq!(
+            payloads(compacted),
+            vec![(0, PayloadKind::Diff, 0), (0, PayloadKind::Snapshot, 2)]
+        );
+    }
+}
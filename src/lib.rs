@@ -81,23 +81,59 @@
 //!
 //! _For a more in-depth example checkout the [examples](https://github.com/entity-sync-rs/track/tree/master/examples) on github._
 
-use std::fmt::Debug;
+use std::{borrow::BorrowMut, fmt::Debug};
 
 use crossbeam_channel::Sender;
+use serde::{de::DeserializeOwned, Serialize};
 use serde_diff::SerdeDiff;
 
+use self::error::ErrorKind;
+
 pub use track_macro::track;
 
 pub use self::{
-    apply::Apply, channel::ModificationChannel, event::ModificationEvent, tracker::Tracker,
+    apply::{Apply, ApplyRegistry},
+    broadcast::{BroadcastModificationChannel, Subscription, SubscriptionId},
+    channel::{BackpressurePolicy, ModificationChannel, ModificationSink},
+    coalesce::CoalescingBuffer,
+    envelope::ENVELOPE_VERSION,
+    event::{ModificationEvent, PayloadKind},
+    field_path::FieldPathMode,
+    history::UndoStack,
+    sequence::{VersionCheck, VersionLedger},
+    tracker::Tracker,
+    type_key::TypeKey,
+    typed::{DecodedDiff, TypedModificationEvent, TypedReceiver},
 };
 
+#[cfg(feature = "field-changes")]
+pub use self::changes::{FieldChange, FieldPathSegment};
+#[cfg(feature = "async")]
+pub use self::stream::ModificationStream;
+
 mod apply;
+mod broadcast;
+#[cfg(feature = "field-changes")]
+mod changes;
 mod channel;
+mod coalesce;
+mod envelope;
 pub mod error;
 mod event;
+mod field_path;
+mod hash;
+mod history;
+mod sequence;
+#[cfg(feature = "async")]
+mod stream;
 mod tracker;
+mod type_key;
+mod typed;
 
+#[cfg(test)]
+mod fixtures;
+
+pub mod journal;
 pub mod serialization;
 
 /// A trait with functions for tracking struct value modifications.
@@ -113,10 +149,68 @@ where
         sender: &'notifier Sender<ModificationEvent<I>>,
         identifier: I,
     ) -> Tracker<'_, 'notifier, C, S, I>;
+
+    /// Tracks modifications and sends them to the given [sink](./trait.ModificationSink.html),
+    /// such as a [ModificationChannel](./struct.ModificationChannel.html) or a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html).
+    ///
+    /// The events are assigned a sequence number and version by these channels.
+    /// The returned tracker uses the [ErrorPolicy](./error/enum.ErrorPolicy.html) configured on the sink.
+    fn track_on<'notifier, I, K>(
+        &mut self,
+        sink: &'notifier K,
+        identifier: I,
+    ) -> Tracker<'_, 'notifier, C, S, I>
+    where
+        Self: BorrowMut<C>,
+        I: Copy + Clone + Send + Sync,
+        K: ModificationSink<I>,
+    {
+        Tracker::new(self.borrow_mut(), sink, S::default(), identifier)
+            .with_error_policy(sink.error_policy().clone())
+    }
+
+    /// Tracks modifications without sending them.
+    ///
+    /// The modifications are returned by [Tracker::finish](./struct.Tracker.html#method.finish) instead, see [Tracker::detached](./struct.Tracker.html#method.detached).
+    fn track_detached<'notifier, I: Copy + Clone + Send + Sync>(
+        &mut self,
+        identifier: I,
+    ) -> Tracker<'_, 'notifier, C, S, I>
+    where
+        Self: BorrowMut<C>,
+    {
+        Tracker::detached(self.borrow_mut(), S::default(), identifier)
+    }
 }
 
 /// A marker trait with a number of requirements that are mandatory for trackable types.
-pub trait TrackableMarker: Clone + SerdeDiff + Debug + Send + Sync + 'static {}
+pub trait TrackableMarker:
+    Clone + SerdeDiff + Serialize + DeserializeOwned + Debug + Send + Sync + 'static
+{
+    /// Returns the [TypeKey](./struct.TypeKey.html) that identifies this type across processes.
+    ///
+    /// By default the key is a hash of the serde name of the type, see [TypeKey::from_serde_name](./struct.TypeKey.html#method.from_serde_name).
+    /// This name does not include the module path, so types with the same name that are sent on one channel need a `#[serde(rename = "name")]`,
+    /// or an implementation of this method that returns a distinct key, such as [TypeKey::from_name](./struct.TypeKey.html#method.from_name) of the full path.
+    /// Registering two types with the same key returns [ErrorKind::DuplicateTypeKey](./error/enum.ErrorKind.html#variant.DuplicateTypeKey).
+    /// Types without a serde name, such as `#[serde(transparent)]` types, must implement this method,
+    /// otherwise it returns [ErrorKind::MissingTypeKey](./error/enum.ErrorKind.html#variant.MissingTypeKey).
+    fn type_key() -> Result<TypeKey, ErrorKind> {
+        TypeKey::from_serde_name::<Self>().ok_or(ErrorKind::MissingTypeKey {
+            type_name: std::any::type_name::<Self>(),
+        })
+    }
+
+    /// Returns the [FieldPathMode](./enum.FieldPathMode.html) in which the diffs of this type are made.
+    ///
+    /// By default fields are identified by their index.
+    /// To identify them by name, which keeps the diffs compatible when fields are added or reordered,
+    /// implement this trait by hand instead of with the `track` attribute and return [FieldPathMode::Name](./enum.FieldPathMode.html#variant.Name).
+    /// The mode can also be set per tracker with [Tracker::with_field_path_mode](./struct.Tracker.html#method.with_field_path_mode).
+    fn field_path_mode() -> FieldPathMode {
+        FieldPathMode::Index
+    }
+}
 
 pub mod re_exports {
     /// A re-export of the [serde](https://crates.io/crates/serde) create.
@@ -142,10 +236,14 @@ pub mod preclude {
 
     pub use track_macro::track;
 
-    pub use crate::{ModificationEvent, Trackable, TrackableMarker, Tracker};
+    pub use crate::{
+        ModificationEvent, ModificationSink, Trackable, TrackableMarker, Tracker, TypeKey,
+    };
     // [serde-diff](https://crates.io/crates/serde-diff)s macro's require `serde_diff` to be imported when we use `track` attribute macro.
     pub use crate::re_exports::serde_diff;
-    pub use crate::serialization::{bincode::Bincode, SerializationStrategy};
+    #[cfg(feature = "bincode-serialization")]
+    pub use crate::serialization::bincode::Bincode;
+    pub use crate::serialization::SerializationStrategy;
 
     pub use self::serde_diff::SerdeDiff;
 }
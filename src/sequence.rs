@@ -0,0 +1,184 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{ModificationEvent, TypeKey};
+
+/// The outcome of checking an event against a [VersionLedger](./struct.VersionLedger.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VersionCheck {
+    /// The event is the next version and can be applied.
+    InOrder,
+    /// The event, or a later version, was already applied.
+    Duplicate,
+    /// One or more earlier versions are missing.
+    /// The event should be buffered until those are applied, or the component should be resynchronized.
+    Gap {
+        /// The version that was expected.
+        expected: u64,
+    },
+    /// The event carries no version because it was not sent through a [ModificationChannel](./struct.ModificationChannel.html).
+    Unversioned,
+}
+
+/// Keeps track of the versions that are applied per identifier and type.
+///
+/// Checking events before they are applied prevents a diff from being applied to the wrong base state
+/// when events are lost, duplicated or reordered.
+pub struct VersionLedger<I> {
+    applied: HashMap<(I, TypeKey), u64>,
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> VersionLedger<I> {
+    /// Constructs a new, empty, ledger.
+    pub fn new() -> VersionLedger<I> {
+        VersionLedger {
+            applied: HashMap::new(),
+        }
+    }
+
+    /// Returns the last applied version for the given identifier and type, or 0 if none was applied.
+    pub fn version(&self, identifier: I, type_key: TypeKey) -> u64 {
+        self.applied
+            .get(&(identifier, type_key))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Checks whether the given event can be applied.
+    pub fn check(&self, event: &ModificationEvent<I>) -> VersionCheck {
+        if event.version == 0 {
+            return VersionCheck::Unversioned;
+        }
+
+        let expected = self.version(event.identifier, event.type_key) + 1;
+
+        if event.version == expected {
+            VersionCheck::InOrder
+        } else if event.version < expected {
+            VersionCheck::Duplicate
+        } else {
+            VersionCheck::Gap { expected }
+        }
+    }
+
+    /// Records the version of the given event as applied.
+    pub fn accept(&mut self, event: &ModificationEvent<I>) {
+        if event.version != 0 {
+            self.applied
+                .insert((event.identifier, event.type_key), event.version);
+        }
+    }
+
+    /// Checks the given event and, if it is in order, records it as applied.
+    pub fn check_and_accept(&mut self, event: &ModificationEvent<I>) -> VersionCheck {
+        let check = self.check(event);
+
+        if check == VersionCheck::InOrder {
+            self.accept(event);
+        }
+
+        check
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync + Eq + Hash> Default for VersionLedger<I> {
+    fn default() -> Self {
+        VersionLedger::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use super::{VersionCheck, VersionLedger};
+    use crate::{fixtures::Position, ModificationChannel, ModificationEvent, TypeKey};
+
+    fn event(identifier: u32, type_key: u64, version: u64) -> ModificationEvent<u32> {
+        let mut event = ModificationEvent::new(
+            Vec::new(),
+            identifier,
+            TypeId::of::<Position>(),
+            TypeKey::from_raw(type_key),
+        );
+        event.version = version;
+        event
+    }
+
+    #[test]
+    fn versions_are_checked_in_order() {
+        let mut ledger = VersionLedger::new();
+
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 1)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 1)),
+            VersionCheck::Duplicate
+        );
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 3)),
+            VersionCheck::Gap { expected: 2 }
+        );
+        assert_eq!(ledger.version(1, TypeKey::from_raw(1)), 1);
+
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 2)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 3)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(ledger.version(1, TypeKey::from_raw(1)), 3);
+    }
+
+    #[test]
+    fn unversioned_events_are_not_recorded() {
+        let mut ledger = VersionLedger::new();
+
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 0)),
+            VersionCheck::Unversioned
+        );
+        assert_eq!(ledger.version(1, TypeKey::from_raw(1)), 0);
+    }
+
+    #[test]
+    fn versions_are_kept_per_identifier_and_type() {
+        let mut ledger = VersionLedger::new();
+
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 1, 1)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(
+            ledger.check_and_accept(&event(2, 1, 1)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(
+            ledger.check_and_accept(&event(1, 2, 1)),
+            VersionCheck::InOrder
+        );
+        assert_eq!(
+            ledger.check(&event(2, 2, 2)),
+            VersionCheck::Gap { expected: 1 }
+        );
+    }
+
+    #[test]
+    fn channel_stamps_global_sequences_and_versions_per_identifier_and_type() {
+        let channel = ModificationChannel::new();
+
+        for &(identifier, type_key) in &[(1, 1), (2, 1), (1, 1), (1, 2), (2, 1)] {
+            channel.send(event(identifier, type_key, 0)).unwrap();
+        }
+
+        let stamps = channel
+            .receiver()
+            .try_iter()
+            .map(|event| (event.sequence, event.version))
+            .collect::<Vec<_>>();
+        assert_eq!(stamps, vec![(1, 1), (2, 1), (3, 2), (4, 1), (5, 2)]);
+    }
+}
@@ -7,8 +7,13 @@
 //! | :----- | :----- |
 //! | `bincode-serialization` | serialization using [bincode](https://crates.io/crates/bincode) (enabled by default) .|
 //! | `rmp-serialization` | serialization using [rmp-serde](https://crates.io/crates/rmp-serde) .|
+//! | `json-serialization` | serialization using [serde_json](https://crates.io/crates/serde_json) .|
+//! | `cbor-serialization` | serialization using [ciborium](https://crates.io/crates/ciborium) .|
+//! | `postcard-serialization` | serialization using [postcard](https://crates.io/crates/postcard) .|
+//! | `compression` | compression of serialized data with [flate2](https://crates.io/crates/flate2), see [Compressed](./compressed/struct.Compressed.html) .|
+//! | `checksum` | CRC32 checksums of serialized data with [crc32fast](https://crates.io/crates/crc32fast), see [Checksummed](./checksummed/struct.Checksummed.html) .|
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::error::ErrorKind;
 use crate::preclude::SerdeDiff;
@@ -25,17 +30,56 @@ pub mod bincode;
 #[cfg(feature = "rmp-serialization")]
 pub mod rmp;
 
+/// Implementation of [SerializationStrategy](./trait.SerializationStrategy.html) for serializing with [serde_json](https://crates.io/crates/serde_json).
+/// It is enabled with the `json-serialization` feature flag.
+///
+/// The serialized data is human readable, which is useful for debugging and web clients.
+#[cfg(feature = "json-serialization")]
+pub mod json;
+
+/// Implementation of [SerializationStrategy](./trait.SerializationStrategy.html) for serializing with [ciborium](https://crates.io/crates/ciborium).
+/// It is enabled with the `cbor-serialization` feature flag.
+#[cfg(feature = "cbor-serialization")]
+pub mod cbor;
+
+/// Implementation of [SerializationStrategy](./trait.SerializationStrategy.html) for serializing with [postcard](https://crates.io/crates/postcard).
+/// It is enabled with the `postcard-serialization` feature flag.
+///
+/// Postcard uses a compact varint encoding, which suits bandwidth constrained links.
+#[cfg(feature = "postcard-serialization")]
+pub mod postcard;
+
+/// Implementation of [SerializationStrategy](./trait.SerializationStrategy.html) that compresses the data of another strategy.
+/// It is enabled with the `compression` feature flag.
+#[cfg(feature = "compression")]
+pub mod compressed;
+
+/// Implementation of [SerializationStrategy](./trait.SerializationStrategy.html) that adds a checksum to the data of another strategy.
+/// It is enabled with the `checksum` feature flag.
+#[cfg(feature = "checksum")]
+pub mod checksummed;
+
 /// An adapter interface with extension methods for serializing purposes used in this crate.
 pub trait SerializationStrategy: Clone + Default + Send + Sync {
     /// Serializes the given type to a byte buffer.
     fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind>;
 
     /// Deserializes the given byte buffer to the desired type.
-    fn deserialize<'a, T: Deserialize<'a>>(&self, buffer: &'a [u8]) -> Result<T, ErrorKind>;
+    ///
+    /// The type can not borrow from the buffer, this allows strategies to transform the buffer before it is deserialized.
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind>;
 
     /// Applies the given byte buffer to the given type.
     /// The buffer contains the data of the modified fields sent with the [ModificationEvent](../../track/struct.ModificationEvent.html).
     fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind>;
+
+    /// Returns the id that identifies this strategy in an [encoded event](../struct.ModificationEvent.html#method.encode).
+    ///
+    /// The strategies in this crate use the ids 1 to 63, 0 means the strategy is unknown.
+    /// Wrapping strategies set a bit on the id of the strategy they wrap: bit 6 for [Compressed](./compressed/struct.Compressed.html) and bit 7 for [Checksummed](./checksummed/struct.Checksummed.html).
+    fn strategy_id(&self) -> u8 {
+        0
+    }
 }
 
 /// A wrapper type over an implementation of [SerializationStrategy](./trait.SerializationStrategy.html).
@@ -53,7 +97,7 @@ impl<S: SerializationStrategy> ModificationSerializer<S> {
         self.strategy.serialize(input)
     }
 
-    pub fn deserialize<'a, T: Deserialize<'a>>(&self, buffer: &'a [u8]) -> Result<T, ErrorKind> {
+    pub fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
         self.strategy.deserialize(buffer)
     }
 }
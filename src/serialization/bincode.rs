@@ -1,4 +1,4 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
 use crate::error::ErrorKind;
@@ -9,22 +9,26 @@ pub struct Bincode;
 
 impl SerializationStrategy for Bincode {
     fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
-        Ok(bincode::serialize(&input)
-            .map_err(|e| ErrorKind::SerializationError(e.to_string()))?)
+        bincode::serialize(&input)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))
     }
 
-    fn deserialize<'a, T: Deserialize<'a>>(&self, buffer: &'a [u8]) -> Result<T, ErrorKind> {
-        Ok(bincode::deserialize::<T>(buffer)
-            .map_err(|e| ErrorKind::SerializationError(e.to_string()))?)
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        bincode::deserialize::<T>(buffer)
+            .map_err(|e| ErrorKind::DeserializationError(Box::new(e)))
     }
 
     fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
         bincode::config()
             .deserialize_seed(serde_diff::Apply::deserializable(component), data)
-            .map_err(|e| ErrorKind::SerializationError(e.to_string()))?;
+            .map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
 
         Ok(())
     }
+
+    fn strategy_id(&self) -> u8 {
+        1
+    }
 }
 
 impl Default for Bincode {
@@ -0,0 +1,64 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
+use crate::error::ErrorKind;
+
+#[derive(Clone, Debug)]
+/// Serialization strategy using ciborium.
+pub struct Cbor;
+
+impl SerializationStrategy for Cbor {
+    fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
+        let mut buffer = Vec::new();
+        ciborium::ser::into_writer(input, &mut buffer)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))?;
+
+        Ok(buffer)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        ciborium::de::from_reader(buffer).map_err(|e| ErrorKind::DeserializationError(Box::new(e)))
+    }
+
+    fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
+        // ciborium does not expose its deserializer, so the diff is decoded into a self-describing value first.
+        let diff: serde_value::Value =
+            ciborium::de::from_reader(data).map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
+        serde_diff::Apply::apply(diff, component)
+            .map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    fn strategy_id(&self) -> u8 {
+        4
+    }
+}
+
+impl Default for Cbor {
+    fn default() -> Self {
+        Cbor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_diff::Diff;
+
+    use super::Cbor;
+    use crate::{fixtures::Position, serialization::SerializationStrategy};
+
+    #[test]
+    fn diff_round_trip() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 3, y: 2 };
+
+        let data = Cbor.serialize(&Diff::serializable(&old, &new)).unwrap();
+        let mut value = old.clone();
+        Cbor.apply_to(&mut value, &data).unwrap();
+        assert_eq!(value, new);
+
+        let data = Cbor.serialize(&new).unwrap();
+        assert_eq!(Cbor.deserialize::<Position>(&data).unwrap(), new);
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
+use crate::error::ErrorKind;
+
+/// The size of the checksum that precedes the data.
+const CHECKSUM_SIZE: usize = 4;
+
+#[derive(Clone, Debug)]
+/// Serialization strategy that adds a CRC32 checksum to the data of another strategy.
+///
+/// The checksum is verified before the data is deserialized or applied.
+/// Corrupted or truncated data results in [ErrorKind::ChecksumMismatch](../../error/enum.ErrorKind.html#variant.ChecksumMismatch),
+/// instead of a vague error or wrongly applied modifications.
+pub struct Checksummed<S: SerializationStrategy> {
+    strategy: S,
+}
+
+impl<S: SerializationStrategy> Checksummed<S> {
+    /// Constructs a strategy that adds a checksum to the data serialized with the given strategy.
+    pub fn new(strategy: S) -> Checksummed<S> {
+        Checksummed { strategy }
+    }
+
+    /// Verifies the checksum of the given payload and returns the data without the checksum.
+    fn verify<'a>(&self, payload: &'a [u8]) -> Result<&'a [u8], ErrorKind> {
+        if payload.len() < CHECKSUM_SIZE {
+            return Err(ErrorKind::DeserializationError(
+                "Payload is shorter than its checksum".into(),
+            ));
+        }
+
+        let (checksum, data) = payload.split_at(CHECKSUM_SIZE);
+        let expected = u32::from_le_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]);
+        let found = crc32fast::hash(data);
+
+        if expected != found {
+            return Err(ErrorKind::ChecksumMismatch { expected, found });
+        }
+
+        Ok(data)
+    }
+}
+
+impl<S: SerializationStrategy> SerializationStrategy for Checksummed<S> {
+    fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
+        let data = self.strategy.serialize(input)?;
+
+        let mut payload = Vec::with_capacity(data.len() + CHECKSUM_SIZE);
+        payload.extend_from_slice(&crc32fast::hash(&data).to_le_bytes());
+        payload.extend_from_slice(&data);
+
+        Ok(payload)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        self.strategy.deserialize(self.verify(buffer)?)
+    }
+
+    fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
+        self.strategy.apply_to(component, self.verify(data)?)
+    }
+
+    fn strategy_id(&self) -> u8 {
+        self.strategy.strategy_id() | 0b1000_0000
+    }
+}
+
+impl<S: SerializationStrategy> Default for Checksummed<S> {
+    fn default() -> Self {
+        Checksummed::new(S::default())
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use serde_diff::Diff;
+
+    use super::Checksummed;
+    use crate::{
+        error::ErrorKind,
+        fixtures::Position,
+        serialization::{bincode::Bincode, SerializationStrategy},
+    };
+
+    #[test]
+    fn round_trip() {
+        let strategy = Checksummed::new(Bincode);
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 3, y: 2 };
+
+        let data = strategy.serialize(&new).unwrap();
+        assert_eq!(strategy.deserialize::<Position>(&data).unwrap(), new);
+
+        let data = strategy.serialize(&Diff::serializable(&old, &new)).unwrap();
+        let mut value = old.clone();
+        strategy.apply_to(&mut value, &data).unwrap();
+        assert_eq!(value, new);
+    }
+
+    #[test]
+    fn flipped_byte_is_a_checksum_mismatch() {
+        let strategy = Checksummed::new(Bincode);
+        let mut data = strategy.serialize(&Position { x: 1, y: 2 }).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+
+        match strategy.deserialize::<Position>(&data) {
+            Err(ErrorKind::ChecksumMismatch { expected, found }) => assert_ne!(expected, found),
+            other => panic!("expected a checksum mismatch, got {:?}", other),
+        }
+
+        let mut value = Position::default();
+        assert!(matches!(
+            strategy.apply_to(&mut value, &data),
+            Err(ErrorKind::ChecksumMismatch { .. })
+        ));
+        assert_eq!(value, Position::default());
+    }
+
+    #[test]
+    fn payload_shorter_than_the_checksum_is_an_error() {
+        let strategy = Checksummed::new(Bincode);
+
+        for length in 0..4 {
+            match strategy.deserialize::<Position>(&vec![0; length]) {
+                Err(ErrorKind::DeserializationError(_)) => {}
+                other => panic!("expected a deserialization error, got {:?}", other),
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::{
+    borrow::Cow,
+    io::{Read, Write},
+};
+
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
+use crate::error::ErrorKind;
+
+/// The header of a payload that is sent as-is.
+const UNCOMPRESSED: u8 = 0;
+/// The header of a payload that is compressed.
+const COMPRESSED: u8 = 1;
+
+#[derive(Clone, Debug)]
+/// Serialization strategy that compresses the data of another strategy with deflate.
+///
+/// Data smaller than the threshold is sent as-is, since compressing it would hardly make a difference.
+/// Each payload starts with a one byte header that marks whether it is compressed.
+///
+/// Decompressed data is limited in size, so that a small malicious payload cannot exhaust the memory of the receiver.
+pub struct Compressed<S: SerializationStrategy> {
+    strategy: S,
+    threshold: usize,
+    limit: usize,
+}
+
+impl<S: SerializationStrategy> Compressed<S> {
+    /// The threshold, in bytes, that is used by default.
+    pub const DEFAULT_THRESHOLD: usize = 256;
+
+    /// The limit, in bytes, of decompressed data that is used by default.
+    pub const DEFAULT_LIMIT: usize = 16 * 1024 * 1024;
+
+    /// Constructs a strategy that compresses data of at least `threshold` bytes, serialized with the given strategy.
+    pub fn new(strategy: S, threshold: usize) -> Compressed<S> {
+        Compressed {
+            strategy,
+            threshold,
+            limit: Self::DEFAULT_LIMIT,
+        }
+    }
+
+    /// Sets the maximum size of decompressed data, larger data fails to deserialize.
+    pub fn with_limit(mut self, limit: usize) -> Compressed<S> {
+        self.limit = limit;
+        self
+    }
+
+    /// Compresses the given data if it reaches the threshold and prepends the header.
+    fn compress(&self, data: Vec<u8>) -> Result<Vec<u8>, ErrorKind> {
+        if data.len() >= self.threshold {
+            let mut encoder = DeflateEncoder::new(vec![COMPRESSED], Compression::fast());
+            encoder
+                .write_all(&data)
+                .map_err(|e| ErrorKind::SerializationError(Box::new(e)))?;
+            let compressed = encoder
+                .finish()
+                .map_err(|e| ErrorKind::SerializationError(Box::new(e)))?;
+
+            // Data that does not compress well is sent as-is.
+            if compressed.len() <= data.len() {
+                return Ok(compressed);
+            }
+        }
+
+        let mut payload = Vec::with_capacity(data.len() + 1);
+        payload.push(UNCOMPRESSED);
+        payload.extend_from_slice(&data);
+
+        Ok(payload)
+    }
+
+    /// Strips the header from the given payload and decompresses it if needed.
+    fn decompress<'a>(&self, payload: &'a [u8]) -> Result<Cow<'a, [u8]>, ErrorKind> {
+        match payload.split_first() {
+            Some((&UNCOMPRESSED, data)) => Ok(Cow::Borrowed(data)),
+            Some((&COMPRESSED, data)) => {
+                let mut decompressed = Vec::new();
+                // One byte more than the limit is read, to tell data of exactly the limit from data that exceeds it.
+                DeflateDecoder::new(data)
+                    .take(self.limit as u64 + 1)
+                    .read_to_end(&mut decompressed)
+                    .map_err(|e| ErrorKind::DeserializationError(Box::new(e)))?;
+
+                if decompressed.len() > self.limit {
+                    return Err(ErrorKind::DeserializationError(
+                        format!(
+                            "Decompressed data exceeds the limit of {} bytes",
+                            self.limit
+                        )
+                        .into(),
+                    ));
+                }
+
+                Ok(Cow::Owned(decompressed))
+            }
+            Some((header, _)) => Err(ErrorKind::DeserializationError(
+                format!("Unknown compression header: {}", header).into(),
+            )),
+            None => Err(ErrorKind::DeserializationError(
+                "Missing compression header".into(),
+            )),
+        }
+    }
+}
+
+impl<S: SerializationStrategy> SerializationStrategy for Compressed<S> {
+    fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
+        self.compress(self.strategy.serialize(input)?)
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        self.strategy.deserialize(&self.decompress(buffer)?)
+    }
+
+    fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
+        self.strategy.apply_to(component, &self.decompress(data)?)
+    }
+
+    fn strategy_id(&self) -> u8 {
+        self.strategy.strategy_id() | 0b0100_0000
+    }
+}
+
+impl<S: SerializationStrategy> Default for Compressed<S> {
+    fn default() -> Self {
+        Compressed::new(S::default(), Self::DEFAULT_THRESHOLD)
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use super::Compressed;
+    use crate::{
+        error::ErrorKind,
+        serialization::{bincode::Bincode, SerializationStrategy},
+    };
+
+    #[test]
+    fn large_data_round_trip() {
+        let strategy = Compressed::new(Bincode, 16);
+        let value = vec![7u8; 4096];
+
+        let data = strategy.serialize(&value).unwrap();
+        assert!(data.len() < value.len());
+        assert_eq!(strategy.deserialize::<Vec<u8>>(&data).unwrap(), value);
+    }
+
+    #[test]
+    fn decompressed_data_is_limited() {
+        let data = Compressed::new(Bincode, 16)
+            .serialize(&vec![0u8; 1024 * 1024])
+            .unwrap();
+
+        match Compressed::new(Bincode, 16)
+            .with_limit(1024)
+            .deserialize::<Vec<u8>>(&data)
+        {
+            Err(ErrorKind::DeserializationError(_)) => {}
+            other => panic!("expected a deserialization error, got {:?}", other),
+        }
+    }
+}
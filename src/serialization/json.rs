@@ -0,0 +1,81 @@
+use serde::{de::{DeserializeOwned, DeserializeSeed}, Serialize};
+
+use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
+use crate::error::ErrorKind;
+
+#[derive(Clone, Debug)]
+/// Serialization strategy using serde_json.
+pub struct Json;
+
+impl SerializationStrategy for Json {
+    fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
+        serde_json::to_vec(&input)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        serde_json::from_slice::<T>(buffer)
+            .map_err(|e| ErrorKind::DeserializationError(Box::new(e)))
+    }
+
+    fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
+        let mut deserializer = serde_json::Deserializer::from_slice(data);
+        serde_diff::Apply::deserializable(component)
+            .deserialize(&mut deserializer)
+            .map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    fn strategy_id(&self) -> u8 {
+        3
+    }
+}
+
+impl Default for Json {
+    fn default() -> Self {
+        Json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_diff::{Config, Diff, FieldPathMode};
+
+    use super::Json;
+    use crate::{fixtures::Position, serialization::SerializationStrategy};
+
+    #[test]
+    fn diff_round_trip() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 3, y: 2 };
+
+        let data = Json.serialize(&Diff::serializable(&old, &new)).unwrap();
+        let mut value = old.clone();
+        Json.apply_to(&mut value, &data).unwrap();
+        assert_eq!(value, new);
+
+        let data = Json.serialize(&new).unwrap();
+        assert_eq!(Json.deserialize::<Position>(&data).unwrap(), new);
+    }
+
+    #[test]
+    fn diff_round_trip_in_each_field_path_mode() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 1, y: 5 };
+
+        for &mode in &[FieldPathMode::Index, FieldPathMode::Name] {
+            let diff = Config::new()
+                .with_field_path_mode(mode)
+                .serializable_diff(&old, &new);
+            let data = Json.serialize(&diff).unwrap();
+
+            let names_field = String::from_utf8(data.clone()).unwrap().contains("\"y\"");
+            assert_eq!(names_field, matches!(mode, FieldPathMode::Name));
+
+            let mut value = old.clone();
+            Json.apply_to(&mut value, &data).unwrap();
+            assert_eq!(value, new);
+        }
+    }
+}
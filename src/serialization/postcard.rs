@@ -0,0 +1,76 @@
+use serde::{de::{DeserializeOwned, DeserializeSeed}, Serialize};
+
+use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
+use crate::error::ErrorKind;
+
+#[derive(Clone, Debug)]
+/// Serialization strategy using postcard.
+pub struct Postcard;
+
+impl SerializationStrategy for Postcard {
+    fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
+        postcard::to_allocvec(&input).map_err(|e| ErrorKind::SerializationError(Box::new(e)))
+    }
+
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
+        postcard::from_bytes::<T>(buffer).map_err(|e| ErrorKind::DeserializationError(Box::new(e)))
+    }
+
+    fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
+        let mut deserializer = postcard::Deserializer::from_bytes(data);
+        serde_diff::Apply::deserializable(component)
+            .deserialize(&mut deserializer)
+            .map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
+
+        Ok(())
+    }
+
+    fn strategy_id(&self) -> u8 {
+        5
+    }
+}
+
+impl Default for Postcard {
+    fn default() -> Self {
+        Postcard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_diff::{Config, Diff, FieldPathMode};
+
+    use super::Postcard;
+    use crate::{fixtures::Position, serialization::SerializationStrategy};
+
+    #[test]
+    fn diff_round_trip() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 3, y: 2 };
+
+        let data = Postcard.serialize(&Diff::serializable(&old, &new)).unwrap();
+        let mut value = old.clone();
+        Postcard.apply_to(&mut value, &data).unwrap();
+        assert_eq!(value, new);
+
+        let data = Postcard.serialize(&new).unwrap();
+        assert_eq!(Postcard.deserialize::<Position>(&data).unwrap(), new);
+    }
+
+    #[test]
+    fn diff_round_trip_in_each_field_path_mode() {
+        let old = Position { x: 1, y: 2 };
+        let new = Position { x: 1, y: 5 };
+
+        for &mode in &[FieldPathMode::Index, FieldPathMode::Name] {
+            let diff = Config::new()
+                .with_field_path_mode(mode)
+                .serializable_diff(&old, &new);
+            let data = Postcard.serialize(&diff).unwrap();
+
+            let mut value = old.clone();
+            Postcard.apply_to(&mut value, &data).unwrap();
+            assert_eq!(value, new);
+        }
+    }
+}
@@ -1,6 +1,4 @@
-use std::error::Error;
-
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
 use crate::{preclude::SerdeDiff, serialization::SerializationStrategy};
 use crate::error::ErrorKind;
@@ -11,23 +9,27 @@ pub struct Rmp;
 
 impl SerializationStrategy for Rmp {
     fn serialize<I: Serialize>(&self, input: &I) -> Result<Vec<u8>, ErrorKind> {
-        Ok(rmp_serde::to_vec(&input)
-            .map_err(|e| ErrorKind::SerializationError(e.description().to_string()))?)
+        rmp_serde::to_vec(&input)
+            .map_err(|e| ErrorKind::SerializationError(Box::new(e)))
     }
 
-    fn deserialize<'a, T: Deserialize<'a>>(&self, buffer: &'a [u8]) -> Result<T, ErrorKind> {
+    fn deserialize<T: DeserializeOwned>(&self, buffer: &[u8]) -> Result<T, ErrorKind> {
         let mut de = rmp_serde::Deserializer::from_read_ref(buffer);
-        Ok(Deserialize::deserialize(&mut de)
-            .map_err(|e| ErrorKind::SerializationError(e.description().to_string()))?)
+        Deserialize::deserialize(&mut de)
+            .map_err(|e| ErrorKind::DeserializationError(Box::new(e)))
     }
 
     fn apply_to<C: SerdeDiff>(&self, component: &mut C, data: &[u8]) -> Result<(), ErrorKind> {
         let mut deserializer = rmp_serde::Deserializer::new(data);
         serde_diff::Apply::apply(&mut deserializer, component)
-            .map_err(|e| ErrorKind::SerializationError(e.description().to_string()))?;
+            .map_err(|e| ErrorKind::ApplyError(Box::new(e)))?;
 
         Ok(())
     }
+
+    fn strategy_id(&self) -> u8 {
+        2
+    }
 }
 
 impl Default for Rmp {
@@ -0,0 +1,170 @@
+use std::{
+    mem,
+    pin::Pin,
+    sync::Mutex,
+    task::{Context, Poll, Waker},
+};
+
+use crossbeam_channel::TryRecvError;
+use futures::{channel::mpsc::UnboundedSender, Stream};
+
+use crate::{error::ErrorKind, ModificationChannel, ModificationEvent, ModificationSink};
+
+/// The wakers of the streams that wait for events on a channel.
+#[derive(Default)]
+pub(crate) struct Wakers(Mutex<Vec<Waker>>);
+
+impl Wakers {
+    /// Registers a waker to be woken by the next event.
+    fn register(&self, waker: &Waker) {
+        let mut wakers = self.0.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !wakers.iter().any(|registered| registered.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    /// Wakes and removes all registered wakers.
+    pub(crate) fn wake_all(&self) {
+        let wakers = mem::take(&mut *self.0.lock().unwrap_or_else(|e| e.into_inner()));
+
+        // Woken outside the lock, since a waker may poll the stream right away.
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// A [Stream](https://docs.rs/futures/0.3/futures/stream/trait.Stream.html) of the events on a [ModificationChannel](./struct.ModificationChannel.html).
+///
+/// It is created with [ModificationChannel::stream](./struct.ModificationChannel.html#method.stream), and is available with the `async` feature.
+/// A waiting stream is woken by events that are sent with [ModificationChannel::send](./struct.ModificationChannel.html#method.send),
+/// which includes the events of trackers created with [Trackable::track_on](./trait.Trackable.html#method.track_on).
+/// Events that are sent directly on the [sender](./struct.ModificationChannel.html#method.sender) do not wake it.
+pub struct ModificationStream<'channel, I: Copy + Clone + Send + Sync> {
+    channel: &'channel ModificationChannel<I>,
+}
+
+impl<I: Copy + Clone + Send + Sync> ModificationChannel<I> {
+    /// Returns a [Stream](./struct.ModificationStream.html) of the events on this channel, which can be awaited without blocking a thread.
+    ///
+    /// The stream shares the receiver of this channel, each event is received either by the stream or by the receiver.
+    pub fn stream(&self) -> ModificationStream<'_, I> {
+        ModificationStream { channel: self }
+    }
+}
+
+impl<'channel, I: Copy + Clone + Send + Sync> Stream for ModificationStream<'channel, I> {
+    type Item = ModificationEvent<I>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.channel.receiver().try_recv() {
+            Ok(event) => return Poll::Ready(Some(event)),
+            Err(TryRecvError::Disconnected) => return Poll::Ready(None),
+            Err(TryRecvError::Empty) => {}
+        }
+
+        self.channel.wakers().register(cx.waker());
+
+        // An event may have been sent before the waker was registered.
+        match self.channel.receiver().try_recv() {
+            Ok(event) => Poll::Ready(Some(event)),
+            Err(TryRecvError::Disconnected) => Poll::Ready(None),
+            Err(TryRecvError::Empty) => Poll::Pending,
+        }
+    }
+}
+
+/// Sends the events to an unbounded [futures channel](https://docs.rs/futures/0.3/futures/channel/mpsc/index.html), which never blocks.
+///
+/// This lets a [Tracker](./struct.Tracker.html) send to an async task without blocking on drop.
+impl<I: Copy + Clone + Send + Sync> ModificationSink<I> for UnboundedSender<ModificationEvent<I>> {
+    fn send(&self, event: ModificationEvent<I>) -> Result<(), ErrorKind> {
+        self.unbounded_send(event)
+            .map_err(|_| ErrorKind::ChannelDisconnected)
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use futures::{
+        channel::mpsc::unbounded, executor::block_on, future::join, poll, task::Poll, StreamExt,
+    };
+
+    use crate::{
+        error::{ErrorKind, ErrorPolicy},
+        fixtures::Position,
+        serialization::bincode::Bincode,
+        Apply, ModificationChannel, Trackable, Tracker,
+    };
+
+    #[test]
+    fn stream_is_woken_by_sent_events() {
+        let channel = ModificationChannel::<u32>::new();
+        let mut stream = channel.stream();
+
+        block_on(async {
+            // Nothing was sent yet, so the stream registers its waker.
+            assert_eq!(
+                poll!(stream.next()).map(|event| event.is_some()),
+                Poll::Pending
+            );
+
+            let consumer = stream.next();
+            let producer = async {
+                let mut position = Position::default();
+                position.track_on(&channel, 1).x = 3;
+            };
+            let (event, ()) = join(consumer, producer).await;
+
+            let mut replica = Position::default();
+            Apply::apply_checked(&mut replica, &event.unwrap(), Bincode).unwrap();
+            assert_eq!(replica.x, 3);
+        });
+    }
+
+    #[test]
+    fn stream_yields_pending_events_in_order() {
+        let channel = ModificationChannel::<u32>::new();
+        let mut position = Position::default();
+
+        for x in 1..=3 {
+            position.track_on(&channel, 1).x = x;
+        }
+
+        let versions = block_on(
+            channel
+                .stream()
+                .take(3)
+                .map(|event| event.version)
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(versions, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn unbounded_sender_is_a_sink() {
+        let (sender, mut receiver) = unbounded();
+
+        block_on(async {
+            let consumer = receiver.next();
+            let producer = async {
+                let mut position = Position::default();
+                Tracker::new(&mut position, &sender, Bincode, 1u32).y = 8;
+            };
+            let (event, ()) = join(consumer, producer).await;
+
+            assert_eq!(event.unwrap().identifier, 1);
+        });
+
+        drop(receiver);
+        let mut position = Position::default();
+        let mut tracker = Tracker::new(&mut position, &sender, Bincode, 1u32)
+            .with_error_policy(ErrorPolicy::Ignore);
+        tracker.y = 9;
+        match tracker.commit() {
+            Err(ErrorKind::ChannelDisconnected) => {}
+            other => panic!("expected a disconnected channel, got {:?}", other),
+        }
+    }
+}
@@ -1,16 +1,21 @@
 use std::any::TypeId;
 use std::ops::{Deref, DerefMut};
 
-use crossbeam_channel::Sender;
-use serde_diff::{Config, Diff, FieldPathMode};
+use serde_diff::{Config, Diff};
 
-use crate::{serialization::SerializationStrategy, TrackableMarker, ModificationEvent};
+use crate::{
+    error::{ErrorKind, ErrorPolicy},
+    serialization::SerializationStrategy,
+    hash, FieldPathMode, ModificationEvent, ModificationSink, TrackableMarker, UndoStack,
+};
 
 /// Tracks value modifications of a type and sends events with these changes.
 ///
 /// The [Tracker](./struct.Tracker.html) implements [DerefMut](./struct.Tracker.html#impl-DerefMut) which makes it possible to treat this tracker as if you are working with the type you track.
 /// On [Drop](./struct.Tracker.html#impl-Drop) it checks if modifications have been made.
-/// If this is the case only the modified fields in an event will be sent to the given sender.
+/// If this is the case only the modified fields in an event will be sent to the given [sink](./trait.ModificationSink.html).
+/// A [detached](./struct.Tracker.html#method.detached) tracker has no sender, its modifications are returned by [finish](./struct.Tracker.html#method.finish) instead.
+/// Errors that occur during the [Drop](./struct.Tracker.html#impl-Drop) are handled by the tracker's [ErrorPolicy](./error/enum.ErrorPolicy.html).
 pub struct Tracker<'borrow, 'notifier, C, S, I>
 where
     C: TrackableMarker,
@@ -19,9 +24,12 @@ where
 {
     old_copy: C,
     borrow: &'borrow mut C,
-    notifier: &'notifier Sender<ModificationEvent<I>>,
+    notifier: Option<&'notifier dyn ModificationSink<I>>,
     serialization: S,
     identifier: I,
+    error_policy: ErrorPolicy,
+    base_hash: bool,
+    field_path_mode: FieldPathMode,
 }
 
 impl<'borrow, 'notifier, C, S, I> Tracker<'borrow, 'notifier, C, S, I>
@@ -33,25 +41,181 @@ where
     /// Constructs a new tracker.
     ///
     /// * `borrow`: mutable reference to the object which modifications are tracked.
-    /// * `notifier`: a sender where mutation events are sent.
+    /// * `notifier`: a [sink](./trait.ModificationSink.html), such as a crossbeam sender or a [ModificationChannel](./struct.ModificationChannel.html), where mutation events are sent.
     /// * `serialization`: an instance of a type that implements [SerializationStrategy](../track/serialization/trait.SerializationStrategy.html) strategy.
-    ///     This serializer is needed to monitor the changes and the serialized mutations are sent along with the event.
+    ///   This serializer is needed to monitor the changes and the serialized mutations are sent along with the event.
     /// * `identifier`: An identifier with which you can relate the modification event to your type.
-    /// The identifier should implement [Identifier](../track/trait.Identifier.html)
+    ///   The identifier should implement [Identifier](../track/trait.Identifier.html)
     pub fn new(
         borrow: &'borrow mut C,
-        notifier: &'notifier Sender<ModificationEvent<I>>,
+        notifier: &'notifier dyn ModificationSink<I>,
         serialization: S,
         identifier: I,
     ) -> Tracker<'borrow, 'notifier, C, S, I> {
         Tracker {
             old_copy: (borrow.deref()).clone(),
             borrow,
-            notifier,
+            notifier: Some(notifier),
             identifier,
             serialization,
+            error_policy: ErrorPolicy::default(),
+            base_hash: false,
+            field_path_mode: C::field_path_mode(),
         }
     }
+
+    /// Constructs a new tracker without a sender.
+    ///
+    /// The modifications are returned by [commit](./struct.Tracker.html#method.commit) and [finish](./struct.Tracker.html#method.finish) instead of being sent.
+    /// Modifications that are neither committed nor finished are discarded on [Drop](./struct.Tracker.html#impl-Drop).
+    ///
+    /// * `borrow`: mutable reference to the object which modifications are tracked.
+    /// * `serialization`: an instance of a type that implements [SerializationStrategy](../track/serialization/trait.SerializationStrategy.html) strategy.
+    /// * `identifier`: An identifier with which you can relate the modification event to your type.
+    pub fn detached(
+        borrow: &'borrow mut C,
+        serialization: S,
+        identifier: I,
+    ) -> Tracker<'borrow, 'notifier, C, S, I> {
+        Tracker {
+            old_copy: (borrow.deref()).clone(),
+            borrow,
+            notifier: None,
+            identifier,
+            serialization,
+            error_policy: ErrorPolicy::default(),
+            base_hash: false,
+            field_path_mode: C::field_path_mode(),
+        }
+    }
+
+    /// Sets the [ErrorPolicy](./error/enum.ErrorPolicy.html) that decides what happens with errors that occur on [Drop](./struct.Tracker.html#impl-Drop).
+    pub fn with_error_policy(
+        mut self,
+        error_policy: ErrorPolicy,
+    ) -> Tracker<'borrow, 'notifier, C, S, I> {
+        self.error_policy = error_policy;
+        self
+    }
+
+    /// Sends the hash of the value before it was modified along with the events.
+    ///
+    /// This allows the receiver to detect that its value has drifted from the sender's value, see [Apply::apply_checked](./struct.Apply.html#method.apply_checked).
+    ///
+    /// The hash is taken over the serialized value, so the tracked type must serialize deterministically.
+    /// Types that contain a `HashMap` or `HashSet` do not, use a `BTreeMap` or `BTreeSet` instead.
+    /// Otherwise equal values can have different hashes, and the receiver reports a mismatch.
+    pub fn with_base_hash(mut self) -> Tracker<'borrow, 'notifier, C, S, I> {
+        self.base_hash = true;
+        self
+    }
+
+    /// Sets the [FieldPathMode](./enum.FieldPathMode.html) of the diffs, which defaults to the mode of the tracked type.
+    pub fn with_field_path_mode(
+        mut self,
+        field_path_mode: FieldPathMode,
+    ) -> Tracker<'borrow, 'notifier, C, S, I> {
+        self.field_path_mode = field_path_mode;
+        self
+    }
+
+    /// Sends the modifications made so far and starts tracking from the current value.
+    ///
+    /// Returns the [ModificationEvent](./struct.ModificationEvent.html), or `None` if nothing was modified.
+    /// Modifications that are committed are not sent again when the tracker is dropped.
+    /// If sending fails the modifications stay pending, so a later commit sends them again.
+    /// A detached tracker only returns the event.
+    pub fn commit(&mut self) -> Result<Option<ModificationEvent<I>>, ErrorKind> {
+        let event = self.modifications()?;
+
+        if let (Some(notifier), Some(event)) = (self.notifier, &event) {
+            notifier.send(event.clone())?;
+        }
+
+        if event.is_some() {
+            self.rebase();
+        }
+
+        Ok(event)
+    }
+
+    /// Stops tracking and returns the modifications as an event instead of sending it.
+    ///
+    /// Returns `None` if nothing was modified.
+    /// Errors are handled by the tracker's [ErrorPolicy](./error/enum.ErrorPolicy.html), in which case `None` is returned as well.
+    /// Either way the modifications are not sent when the tracker is dropped.
+    pub fn finish(mut self) -> Option<ModificationEvent<I>> {
+        let modifications = self.modifications();
+        self.rebase();
+
+        match modifications {
+            Ok(event) => event,
+            Err(e) => {
+                self.error_policy.handle(e);
+                None
+            }
+        }
+    }
+
+    /// [Commits](./struct.Tracker.html#method.commit) the modifications made so far, and records them in the given history.
+    ///
+    /// The modifications are only recorded once they are sent, so a failed commit leaves the history untouched.
+    /// Call this on multiple trackers between [UndoStack::begin_group](./struct.UndoStack.html#method.begin_group) and [UndoStack::end_group](./struct.UndoStack.html#method.end_group) to undo their modifications in one step.
+    pub fn commit_to(
+        &mut self,
+        history: &mut UndoStack<C, I, S>,
+    ) -> Result<Option<ModificationEvent<I>>, ErrorKind> {
+        let before = self.old_copy.clone();
+        let event = self.commit()?;
+
+        if event.is_some() {
+            history.record(self.identifier, &before, self.borrow)?;
+        }
+
+        Ok(event)
+    }
+
+    /// Reverts the tracked value to the state of the last commit, or to its initial state if there was no commit.
+    ///
+    /// No event is sent for the reverted modifications.
+    pub fn rollback(&mut self) {
+        *self.borrow = self.old_copy.clone();
+    }
+
+    /// Packs the modified fields into an event, if there are any.
+    ///
+    /// The tracked baseline is left untouched, see [rebase](#method.rebase).
+    fn modifications(&self) -> Result<Option<ModificationEvent<I>>, ErrorKind> {
+        let diff = Config::new()
+            .with_field_path_mode(self.field_path_mode.into())
+            .serializable_diff(&self.old_copy, self.borrow);
+
+        // `has_changes` is only known after the diff is serialized.
+        let data = self.serialization.serialize::<Diff<C>>(&diff)?;
+
+        if !diff.has_changes() {
+            return Ok(None);
+        }
+
+        let base_hash = if self.base_hash {
+            Some(hash::base_hash(&self.old_copy, &self.serialization)?)
+        } else {
+            None
+        };
+
+        let mut event =
+            ModificationEvent::new(data, self.identifier, TypeId::of::<C>(), C::type_key()?);
+        event.strategy_id = self.serialization.strategy_id();
+        event.base_hash = base_hash;
+        event.field_path_mode = self.field_path_mode;
+
+        Ok(Some(event))
+    }
+
+    /// Starts tracking from the current value.
+    fn rebase(&mut self) {
+        self.old_copy = (*self.borrow).clone();
+    }
 }
 
 impl<'borrow, 'notifier, C, S, I> Deref for Tracker<'borrow, 'notifier, C, S, I>
@@ -64,7 +228,7 @@ where
 
     /// Returns a reference to the underlying type being tracked.
     fn deref(&self) -> &Self::Target {
-        &self.borrow
+        self.borrow
     }
 }
 
@@ -76,7 +240,7 @@ where
 {
     /// Returns a mutable reference to the underlying type being tracked.
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.borrow
+        self.borrow
     }
 }
 
@@ -88,25 +252,237 @@ where
 {
     /// Checks to see if any field values have changed.
     /// If this is the case, the changed fields will be packed into an event and an event will be sent.
+    /// A detached tracker discards its remaining modifications.
     fn drop(&mut self) {
-        let diff = Config::new()
-            .with_field_path_mode(FieldPathMode::Index)
-            .serializable_diff(&self.old_copy, &self.borrow);
-
-        match self.serialization.serialize::<Diff<C>>(&diff) {
-            Ok(data) => {
-                if diff.has_changes() {
-                    self.notifier
-                        .send(ModificationEvent::new(data, self.identifier, TypeId::of::<C>()))
-                        .expect("The sender for modification events panicked. Is the receiver still alive?");
-                }
-            }
-            Err(e) => {
-                panic!(
-                    "Could not serialize modification information because: {:?}",
-                    e
-                );
+        if let Err(e) = self.commit() {
+            self.error_policy.handle(e);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::sync::Mutex;
+
+    use crossbeam_channel::unbounded;
+    use serde::{Deserialize, Serialize};
+    use serde_diff::SerdeDiff;
+
+    use crate::{
+        error::{ErrorKind, ErrorPolicy},
+        fixtures::Position,
+        serialization::bincode::Bincode,
+        Apply, FieldPathMode, ModificationEvent, ModificationSink, TrackableMarker, Tracker,
+        UndoStack,
+    };
+
+    /// A type without a serde name, so it has no default type key.
+    #[derive(Clone, Debug, Default, SerdeDiff, Serialize, Deserialize)]
+    #[serde(transparent)]
+    struct Meters {
+        value: u32,
+    }
+
+    impl TrackableMarker for Meters {}
+
+    /// A type whose diffs identify fields by name, like the `track` attribute with `field_path = "name"` implements it.
+    #[derive(Clone, Debug, Default, PartialEq, SerdeDiff, Serialize, Deserialize)]
+    struct Named {
+        x: u32,
+        y: u32,
+    }
+
+    impl TrackableMarker for Named {
+        fn field_path_mode() -> FieldPathMode {
+            FieldPathMode::Name
+        }
+    }
+
+    /// A sink that fails until it is told to accept events.
+    struct FlakySink {
+        accept: Mutex<bool>,
+        events: Mutex<Vec<ModificationEvent<u32>>>,
+    }
+
+    impl ModificationSink<u32> for FlakySink {
+        fn send(&self, event: ModificationEvent<u32>) -> Result<(), ErrorKind> {
+            if *self.accept.lock().unwrap() {
+                self.events.lock().unwrap().push(event);
+                Ok(())
+            } else {
+                Err(ErrorKind::ChannelDisconnected)
             }
+        }
+    }
+
+    #[test]
+    fn commit_sends_modifications_once() {
+        let (sender, receiver) = unbounded();
+        let mut position = Position::default();
+
+        {
+            let mut tracker = Tracker::new(&mut position, &sender, Bincode, 1u32);
+            tracker.x = 5;
+            assert!(tracker.commit().unwrap().is_some());
+            assert!(tracker.commit().unwrap().is_none());
+        }
+
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn failed_send_keeps_modifications_pending() {
+        let sink = FlakySink {
+            accept: Mutex::new(false),
+            events: Mutex::new(Vec::new()),
+        };
+        let mut position = Position::default();
+
+        {
+            let mut tracker = Tracker::new(&mut position, &sink, Bincode, 1u32);
+            tracker.x = 5;
+            assert!(tracker.commit().is_err());
+
+            *sink.accept.lock().unwrap() = true;
+            assert!(tracker.commit().unwrap().is_some());
+        }
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+
+        let mut replica = Position::default();
+        Apply::apply_to(&mut replica, &events[0].modified_fields, Bincode).unwrap();
+        assert_eq!(replica, position);
+    }
+
+    #[test]
+    fn failed_commit_is_not_recorded_in_the_history() {
+        let sink = FlakySink {
+            accept: Mutex::new(false),
+            events: Mutex::new(Vec::new()),
         };
+        let mut history = UndoStack::<Position, u32, Bincode>::new(Bincode);
+        let mut position = Position::default();
+
+        {
+            let mut tracker = Tracker::new(&mut position, &sink, Bincode, 0u32);
+            tracker.x = 5;
+            assert!(tracker.commit_to(&mut history).is_err());
+            assert!(!history.can_undo());
+
+            *sink.accept.lock().unwrap() = true;
+            assert!(tracker.commit_to(&mut history).unwrap().is_some());
+        }
+
+        let mut storage = [position];
+        assert!(history
+            .undo(&mut storage, |storage: &mut [Position; 1], identifier: &u32| {
+                storage.get_mut(*identifier as usize)
+            })
+            .unwrap());
+        assert_eq!(storage, [Position::default()]);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn rollback_restores_the_value_without_sending() {
+        let (sender, receiver) = unbounded();
+        let mut position = Position { x: 1, y: 2 };
+
+        {
+            let mut tracker = Tracker::new(&mut position, &sender, Bincode, 1u32);
+            tracker.x = 5;
+            tracker.y = 6;
+            tracker.rollback();
+        }
+
+        assert_eq!(position, Position { x: 1, y: 2 });
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn rollback_keeps_committed_modifications() {
+        let (sender, receiver) = unbounded();
+        let mut position = Position::default();
+
+        {
+            let mut tracker = Tracker::new(&mut position, &sender, Bincode, 1u32);
+            tracker.x = 5;
+            tracker.commit().unwrap();
+            tracker.y = 6;
+            tracker.rollback();
+        }
+
+        assert_eq!(position, Position { x: 5, y: 0 });
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+
+    #[test]
+    fn detached_tracker_returns_modifications() {
+        let mut position = Position::default();
+
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut position, Bincode, 1);
+        tracker.y = 3;
+        let event = tracker.finish().unwrap();
+
+        assert_eq!(event.identifier, 1);
+        assert!(Tracker::<_, _, u32>::detached(&mut position, Bincode, 1)
+            .finish()
+            .is_none());
+    }
+
+    #[test]
+    fn failed_finish_is_reported_once() {
+        let (sender, receiver) = unbounded();
+        let (error_sender, error_receiver) = unbounded();
+        let mut meters = Meters::default();
+
+        let mut tracker = Tracker::new(&mut meters, &sender, Bincode, 1u32)
+            .with_error_policy(ErrorPolicy::Channel(error_sender));
+        tracker.value = 5;
+        assert!(tracker.finish().is_none());
+
+        assert_eq!(error_receiver.try_iter().count(), 1);
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn missing_type_key_is_handled_by_the_error_policy() {
+        let (sender, receiver) = unbounded();
+        let (error_sender, error_receiver) = unbounded();
+        let mut meters = Meters::default();
+
+        {
+            let mut tracker = Tracker::new(&mut meters, &sender, Bincode, 1u32)
+                .with_error_policy(ErrorPolicy::Channel(error_sender));
+            tracker.value = 5;
+        }
+
+        match error_receiver.try_recv() {
+            Ok(ErrorKind::MissingTypeKey { type_name }) => assert!(type_name.ends_with("Meters")),
+            other => panic!("expected a missing type key, got {:?}", other),
+        }
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn field_path_mode_of_the_type_is_used() {
+        let mut named = Named::default();
+        let mut tracker = Tracker::<_, _, u32>::detached(&mut named, Bincode, 1);
+        tracker.y = 5;
+        let by_name = tracker.commit().unwrap().unwrap();
+
+        let mut tracker = tracker.with_field_path_mode(FieldPathMode::Index);
+        tracker.y = 6;
+        let by_index = tracker.commit().unwrap().unwrap();
+
+        assert_eq!(by_name.field_path_mode, FieldPathMode::Name);
+        assert_eq!(by_index.field_path_mode, FieldPathMode::Index);
+
+        let mut replica = Named::default();
+        Apply::apply_checked(&mut replica, &by_name, Bincode).unwrap();
+        assert_eq!(replica, Named { x: 0, y: 5 });
+        Apply::apply_checked(&mut replica, &by_index, Bincode).unwrap();
+        assert_eq!(replica, Named { x: 0, y: 6 });
     }
 }
@@ -0,0 +1,189 @@
+use std::fmt::{self, Display, Formatter};
+
+use serde::{
+    de::{self, DeserializeOwned, Visitor},
+    forward_to_deserialize_any, Deserializer,
+};
+
+use crate::{error::ErrorKind, hash::fnv1a, TrackableMarker};
+
+/// A key that identifies a trackable type.
+///
+/// Unlike [TypeId](https://doc.rust-lang.org/std/any/struct.TypeId.html), this key is stable across compilations and binaries,
+/// which makes it possible to route [modification events](./struct.ModificationEvent.html) that were sent over the network or stored on disk.
+/// The key of a type is returned by [TrackableMarker::type_key](./trait.TrackableMarker.html#method.type_key).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TypeKey(u64);
+
+impl TypeKey {
+    /// Constructs a type key from a raw value.
+    pub fn from_raw(value: u64) -> TypeKey {
+        TypeKey(value)
+    }
+
+    /// Constructs a type key by hashing the given name.
+    pub fn from_name(name: &str) -> TypeKey {
+        TypeKey(fnv1a(name.as_bytes()))
+    }
+
+    /// Returns the type key of `C`, see [TrackableMarker::type_key](./trait.TrackableMarker.html#method.type_key).
+    pub fn of<C: TrackableMarker>() -> Result<TypeKey, ErrorKind> {
+        C::type_key()
+    }
+
+    /// Constructs a type key by hashing the serde name of `C`.
+    ///
+    /// The serde name is the name of the struct or enum, or the name given with `#[serde(rename = "name")]`.
+    /// Returns `None` if `C` does not deserialize from a named container,
+    /// such as types with `#[serde(transparent)]`, untagged or internally tagged enums, and most hand written implementations.
+    pub fn from_serde_name<C: DeserializeOwned>() -> Option<TypeKey> {
+        serde_name::<C>().map(TypeKey::from_name)
+    }
+
+    /// Returns the raw value of this key.
+    pub fn value(self) -> u64 {
+        self.0
+    }
+}
+
+impl Display for TypeKey {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{:016x}", self.0)
+    }
+}
+
+/// Returns the name that `C` passes to the deserializer for its container.
+fn serde_name<C: DeserializeOwned>() -> Option<&'static str> {
+    let mut name = None;
+    // The probe always fails after it has seen the name.
+    let _ = C::deserialize(NameProbe { name: &mut name });
+    name
+}
+
+/// A deserializer that records the container name it is asked for, without deserializing anything.
+struct NameProbe<'a> {
+    name: &'a mut Option<&'static str>,
+}
+
+impl<'a> NameProbe<'a> {
+    fn record<T>(self, name: &'static str) -> Result<T, Probed> {
+        *self.name = Some(name);
+        Err(Probed)
+    }
+}
+
+impl<'de, 'a> Deserializer<'de> for NameProbe<'a> {
+    type Error = Probed;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Probed> {
+        Err(Probed)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        self.record(name)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        self.record(name)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        self.record(name)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        self.record(name)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Probed> {
+        self.record(name)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit seq tuple map identifier ignored_any
+    }
+}
+
+/// The error with which the [NameProbe](struct.NameProbe.html) stops the deserialization.
+#[derive(Debug)]
+struct Probed;
+
+impl Display for Probed {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        write!(fmt, "probed the serde name")
+    }
+}
+
+impl std::error::Error for Probed {}
+
+impl de::Error for Probed {
+    fn custom<T: Display>(_msg: T) -> Self {
+        Probed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::TypeKey;
+    use crate::fixtures::Position;
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Position")]
+    struct Renamed {
+        _x: u32,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Shape {
+        Circle(f32),
+        Square(f32),
+    }
+
+    #[derive(Deserialize)]
+    #[serde(transparent)]
+    struct Transparent(#[allow(dead_code)] u32);
+
+    #[test]
+    fn key_is_derived_from_the_serde_name() {
+        assert_eq!(
+            TypeKey::from_serde_name::<Position>(),
+            Some(TypeKey::from_name("Position"))
+        );
+        assert_eq!(
+            TypeKey::from_serde_name::<Renamed>(),
+            Some(TypeKey::from_name("Position"))
+        );
+        assert_eq!(
+            TypeKey::from_serde_name::<Shape>(),
+            Some(TypeKey::from_name("Shape"))
+        );
+        assert_eq!(TypeKey::from_serde_name::<Transparent>(), None);
+    }
+}
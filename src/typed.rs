@@ -0,0 +1,405 @@
+use std::{fmt, marker::PhantomData};
+
+use crossbeam_channel::Receiver;
+use serde_diff::{Config, Diff};
+
+use crate::{
+    error::{ErrorKind, TypedRecvError},
+    serialization::SerializationStrategy,
+    Apply, BroadcastModificationChannel, FieldPathMode, ModificationChannel, ModificationEvent,
+    Subscription, TrackableMarker,
+};
+
+type ApplyFn<C, I> = fn(&mut C, &ModificationEvent<I>) -> Result<(), ErrorKind>;
+
+/// A [modification event](./struct.ModificationEvent.html) of type `C` that can be applied without passing its serialization strategy.
+///
+/// A serialized serde-diff diff only describes its values in terms of the value it applies to,
+/// so it is [decoded](./struct.TypedModificationEvent.html#method.decode) against that value into a [DecodedDiff](./struct.DecodedDiff.html).
+/// Typed events are received from a [TypedReceiver](./struct.TypedReceiver.html).
+#[derive(Clone)]
+pub struct TypedModificationEvent<C, I>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+{
+    event: ModificationEvent<I>,
+    apply: ApplyFn<C, I>,
+}
+
+impl<C, I> TypedModificationEvent<C, I>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+{
+    /// Constructs a typed event from an event that was serialized with strategy `S`.
+    ///
+    /// Returns [ErrorKind::TypeMismatch](./error/enum.ErrorKind.html#variant.TypeMismatch) if the event was made for another type,
+    /// and [ErrorKind::StrategyMismatch](./error/enum.ErrorKind.html#variant.StrategyMismatch) if it was serialized with another strategy.
+    /// Events with an unknown strategy id, 0, are assumed to be serialized with `S`.
+    pub fn from_event<S: SerializationStrategy>(
+        event: ModificationEvent<I>,
+    ) -> Result<TypedModificationEvent<C, I>, ErrorKind> {
+        Self::check::<S>(&event)?;
+
+        Ok(TypedModificationEvent {
+            event,
+            apply: apply_with::<C, S, I>,
+        })
+    }
+
+    /// Checks that the event is of type `C` and serialized with strategy `S`.
+    fn check<S: SerializationStrategy>(event: &ModificationEvent<I>) -> Result<(), ErrorKind> {
+        let type_key = C::type_key()?;
+        if event.type_key != type_key {
+            return Err(ErrorKind::TypeMismatch {
+                expected: type_key,
+                found: event.type_key,
+            });
+        }
+
+        let strategy_id = S::default().strategy_id();
+        if event.strategy_id != 0 && event.strategy_id != strategy_id {
+            return Err(ErrorKind::StrategyMismatch {
+                expected: strategy_id,
+                found: event.strategy_id,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the identifier of the modified value.
+    pub fn identifier(&self) -> I {
+        self.event.identifier
+    }
+
+    /// Returns the untyped event.
+    pub fn event(&self) -> &ModificationEvent<I> {
+        &self.event
+    }
+
+    /// Returns the untyped event, for example to forward it to other processes.
+    pub fn into_event(self) -> ModificationEvent<I> {
+        self.event
+    }
+
+    /// Applies the modifications to the given value, see [Apply::apply_checked](./struct.Apply.html#method.apply_checked).
+    pub fn apply(&self, component: &mut C) -> Result<(), ErrorKind> {
+        (self.apply)(component, &self.event)
+    }
+
+    /// Returns a copy of `base` with the modifications applied.
+    pub fn applied_to(&self, base: &C) -> Result<C, ErrorKind> {
+        let mut component = base.clone();
+        self.apply(&mut component)?;
+        Ok(component)
+    }
+
+    /// Decodes the modifications against `base`, the value they apply to.
+    pub fn decode(&self, base: &C) -> Result<DecodedDiff<C>, ErrorKind> {
+        Ok(DecodedDiff {
+            modified: self.applied_to(base)?,
+            base: base.clone(),
+            field_path_mode: self.event.field_path_mode,
+        })
+    }
+}
+
+/// The modifications of a [typed event](./struct.TypedModificationEvent.html), decoded against the value they apply to.
+#[derive(Clone, Debug)]
+pub struct DecodedDiff<C: TrackableMarker> {
+    base: C,
+    modified: C,
+    field_path_mode: FieldPathMode,
+}
+
+impl<C: TrackableMarker> DecodedDiff<C> {
+    /// Returns the serde-diff [Diff](https://docs.rs/serde-diff/0.2/serde_diff/struct.Diff.html) of the modifications,
+    /// in the [FieldPathMode](./enum.FieldPathMode.html) of the event.
+    ///
+    /// The diff can be serialized again, for example with another strategy.
+    pub fn diff(&self) -> Diff<'_, '_, C> {
+        Config::new()
+            .with_field_path_mode(self.field_path_mode.into())
+            .serializable_diff(&self.base, &self.modified)
+    }
+
+    /// Returns the value the modifications were decoded against.
+    pub fn base(&self) -> &C {
+        &self.base
+    }
+
+    /// Returns the value with the modifications applied.
+    pub fn modified(&self) -> &C {
+        &self.modified
+    }
+
+    /// Returns the value with the modifications applied.
+    pub fn into_modified(self) -> C {
+        self.modified
+    }
+}
+
+impl<C, I> fmt::Debug for TypedModificationEvent<C, I>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TypedModificationEvent")
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+fn apply_with<C, S, I>(component: &mut C, event: &ModificationEvent<I>) -> Result<(), ErrorKind>
+where
+    C: TrackableMarker,
+    S: SerializationStrategy,
+    I: Copy + Clone + Send + Sync,
+{
+    Apply::apply_checked(component, event, S::default())
+}
+
+/// Receives the events of type `C`, serialized with strategy `S`, as [typed events](./struct.TypedModificationEvent.html).
+///
+/// It is created with [ModificationChannel::typed_receiver](./struct.ModificationChannel.html#method.typed_receiver)
+/// or [BroadcastModificationChannel::subscribe_typed](./struct.BroadcastModificationChannel.html#method.subscribe_typed).
+pub struct TypedReceiver<C, I, S>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    receiver: Receiver<ModificationEvent<I>>,
+    /// Keeps the subscriber registered if the events are received from a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html).
+    _subscription: Option<Subscription<I>>,
+    phantom: PhantomData<(C, S)>,
+}
+
+impl<C, I, S> TypedReceiver<C, I, S>
+where
+    C: TrackableMarker,
+    I: Copy + Clone + Send + Sync,
+    S: SerializationStrategy,
+{
+    fn new(
+        receiver: Receiver<ModificationEvent<I>>,
+        subscription: Option<Subscription<I>>,
+    ) -> TypedReceiver<C, I, S> {
+        TypedReceiver {
+            receiver,
+            _subscription: subscription,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Blocks until an event is received.
+    ///
+    /// Returns [TypedRecvError::Rejected](./error/enum.TypedRecvError.html#variant.Rejected), with the event,
+    /// if the received event is of another type or was serialized with another strategy,
+    /// and [TypedRecvError::Disconnected](./error/enum.TypedRecvError.html#variant.Disconnected) if no more events can be received.
+    pub fn recv(&self) -> Result<TypedModificationEvent<C, I>, TypedRecvError<I>> {
+        let event = self
+            .receiver
+            .recv()
+            .map_err(|_| TypedRecvError::Disconnected)?;
+
+        Self::typed(event)
+    }
+
+    /// Returns an iterator over the events that are waiting, without blocking.
+    ///
+    /// Rejected events are returned as [TypedRecvError::Rejected](./error/enum.TypedRecvError.html#variant.Rejected).
+    pub fn try_iter(
+        &self,
+    ) -> impl Iterator<Item = Result<TypedModificationEvent<C, I>, TypedRecvError<I>>> + '_ {
+        self.receiver.try_iter().map(Self::typed)
+    }
+
+    fn typed(
+        event: ModificationEvent<I>,
+    ) -> Result<TypedModificationEvent<C, I>, TypedRecvError<I>> {
+        match TypedModificationEvent::<C, I>::check::<S>(&event) {
+            Ok(()) => Ok(TypedModificationEvent {
+                event,
+                apply: apply_with::<C, S, I>,
+            }),
+            Err(e) => Err(TypedRecvError::Rejected(e, event)),
+        }
+    }
+
+    /// Returns the underlying receiver of untyped events.
+    pub fn receiver(&self) -> &Receiver<ModificationEvent<I>> {
+        &self.receiver
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync> ModificationChannel<I> {
+    /// Returns a receiver that yields the events on this channel as [typed events](./struct.TypedModificationEvent.html) of type `C`.
+    ///
+    /// The typed receiver shares the receiver of this channel, so it should only be used if the channel carries a single type.
+    /// Events of other types are received as [TypedRecvError::Rejected](./error/enum.TypedRecvError.html#variant.Rejected), which returns the event,
+    /// use a [BroadcastModificationChannel](./struct.BroadcastModificationChannel.html#method.subscribe_typed) to receive only the events of one type.
+    pub fn typed_receiver<C, S>(&self) -> TypedReceiver<C, I, S>
+    where
+        C: TrackableMarker,
+        S: SerializationStrategy,
+    {
+        TypedReceiver::new(self.receiver().clone(), None)
+    }
+}
+
+impl<I: Copy + Clone + Send + Sync> BroadcastModificationChannel<I> {
+    /// Adds a subscriber that receives the events of type `C`, sent from now on, as [typed events](./struct.TypedModificationEvent.html).
+    ///
+    /// The subscriber is removed once the typed receiver is dropped.
+    pub fn subscribe_typed<C, S>(&self) -> Result<TypedReceiver<C, I, S>, ErrorKind>
+    where
+        C: TrackableMarker,
+        S: SerializationStrategy,
+    {
+        let subscription = self.subscribe_type::<C>()?;
+
+        Ok(TypedReceiver::new(
+            subscription.receiver().clone(),
+            Some(subscription),
+        ))
+    }
+}
+
+#[cfg(all(test, feature = "bincode-serialization"))]
+mod tests {
+    use std::any::TypeId;
+
+    use crossbeam_channel::unbounded;
+
+    use super::TypedModificationEvent;
+    use crate::{
+        error::{ErrorKind, TypedRecvError},
+        fixtures::{Health, Position},
+        serialization::{bincode::Bincode, SerializationStrategy},
+        Apply, BroadcastModificationChannel, FieldPathMode, ModificationChannel, ModificationEvent,
+        TrackableMarker, Tracker, TypeKey,
+    };
+
+    /// Returns the event of setting `x` on `value`.
+    fn tracked_event(mut value: Position, x: u32) -> ModificationEvent<u32> {
+        let (sender, receiver) = unbounded();
+
+        let mut tracker = Tracker::new(&mut value, &sender, Bincode, 1);
+        tracker.x = x;
+        drop(tracker);
+
+        receiver.try_recv().unwrap()
+    }
+
+    #[test]
+    fn decoded_diff_is_applied() {
+        let base = Position::default();
+        let event = tracked_event(base.clone(), 10);
+        let typed = TypedModificationEvent::<Position, u32>::from_event::<Bincode>(event).unwrap();
+
+        let decoded = typed.decode(&base).unwrap();
+        assert_eq!(decoded.base(), &base);
+        assert_eq!(decoded.modified(), &Position { x: 10, y: 0 });
+
+        let modified_fields = Bincode.serialize(&decoded.diff()).unwrap();
+        let mut position = base.clone();
+        Apply::apply_to(&mut position, &modified_fields, Bincode).unwrap();
+        assert_eq!(position, decoded.into_modified());
+    }
+
+    #[test]
+    fn decoded_diff_keeps_the_field_path_mode() {
+        let base = Position::default();
+        let diff_in = |field_path_mode| {
+            let mut event = tracked_event(base.clone(), 10);
+            event.field_path_mode = field_path_mode;
+            let typed =
+                TypedModificationEvent::<Position, u32>::from_event::<Bincode>(event).unwrap();
+            Bincode
+                .serialize(&typed.decode(&base).unwrap().diff())
+                .unwrap()
+        };
+
+        let by_index = diff_in(FieldPathMode::Index);
+        let by_name = diff_in(FieldPathMode::Name);
+        assert_ne!(by_index, by_name);
+        assert_eq!(by_index, tracked_event(base.clone(), 10).modified_fields);
+    }
+
+    #[test]
+    fn other_type_is_rejected() {
+        let event = tracked_event(Position::default(), 10);
+
+        match TypedModificationEvent::<Health, u32>::from_event::<Bincode>(event) {
+            Err(ErrorKind::TypeMismatch { expected, found }) => {
+                assert_eq!(expected, Health::type_key().unwrap());
+                assert_eq!(found, Position::type_key().unwrap());
+            }
+            other => panic!("expected a type mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn other_strategy_is_rejected() {
+        let mut event = tracked_event(Position::default(), 10);
+        event.strategy_id = Bincode.strategy_id() + 1;
+
+        match TypedModificationEvent::<Position, u32>::from_event::<Bincode>(event) {
+            Err(ErrorKind::StrategyMismatch { expected, found }) => {
+                assert_eq!(expected, Bincode.strategy_id());
+                assert_eq!(found, Bincode.strategy_id() + 1);
+            }
+            other => panic!("expected a strategy mismatch, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn unknown_strategy_is_accepted() {
+        let mut event = ModificationEvent::new(
+            Vec::new(),
+            1,
+            TypeId::of::<Position>(),
+            TypeKey::of::<Position>().unwrap(),
+        );
+        event.strategy_id = 0;
+
+        assert!(TypedModificationEvent::<Position, u32>::from_event::<Bincode>(event).is_ok());
+    }
+
+    #[test]
+    fn typed_subscription_is_removed_once_dropped() {
+        let channel = BroadcastModificationChannel::new();
+        let receiver = channel.subscribe_typed::<Position, Bincode>().unwrap();
+
+        channel
+            .send(tracked_event(Position::default(), 10))
+            .unwrap();
+        assert_eq!(receiver.recv().unwrap().identifier(), 1);
+        assert_eq!(channel.subscriber_count(), 1);
+
+        drop(receiver);
+        assert_eq!(channel.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn typed_receiver_returns_rejected_events() {
+        let channel = ModificationChannel::new();
+        let receiver = channel.typed_receiver::<Health, Bincode>();
+
+        channel
+            .send(tracked_event(Position::default(), 10))
+            .unwrap();
+
+        match receiver.recv() {
+            Err(TypedRecvError::Rejected(ErrorKind::TypeMismatch { .. }, event)) => {
+                assert_eq!(event.type_key, Position::type_key().unwrap());
+                assert_eq!(event.sequence, 1);
+            }
+            other => panic!("expected a rejected event, got {:?}", other),
+        }
+    }
+}